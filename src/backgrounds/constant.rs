@@ -0,0 +1,22 @@
+use crate::{utility::math::vector::Vec3, light::Spectrum};
+use super::traits::BackgroundLike;
+
+/// The same color in every direction. A black background is what a scene without one
+/// gets.
+pub struct ConstantBackground {
+    color: Spectrum,
+}
+
+impl ConstantBackground {
+    pub fn new(color: Spectrum) -> Self {
+        Self {
+            color,
+        }
+    }
+}
+
+impl BackgroundLike for ConstantBackground {
+    fn radiance(&self, _direction: &Vec3) -> Spectrum {
+        self.color.clone()
+    }
+}
@@ -0,0 +1,202 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::{
+        math::{float::{Float, FloatConstants}, vector::Vec3},
+        image::DecodedImage,
+        post_process::luminance,
+        rng::RandomNumberGenerator
+    },
+    light::Spectrum,
+    sampler::PiecewiseConstant2D
+};
+use super::traits::BackgroundLike;
+
+// E==== IMPORTS }}}1
+
+/// An image of everything around the scene, usually a photograph in HDR, stored in the
+/// latitude-longitude (equirectangular) layout. Its columns go around the $y$-axis, the
+/// left edge looking along $+x$ and a quarter of the way across looking along $+z$. Its
+/// rows go from straight up at the top to straight down at the bottom.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    /// Linear colors, row by row starting from the top.
+    texels: Vec<Spectrum>,
+    /// How far (in radians) the map is turned about the $y$-axis, in the direction from
+    /// $+x$ towards $+z$.
+    rotation: Float,
+    /// Multiplies the values in the image.
+    strength: Float,
+    /// Over the image, proportional to how much light each texel sends into the scene.
+    distribution: PiecewiseConstant2D,
+}
+
+impl EnvironmentMap {
+    /// `texels` are linear colors, row by row starting from the top.
+    pub fn new(width: usize, height: usize, texels: Vec<Spectrum>, rotation: Float, strength: Float) -> Self {
+        assert_eq!(texels.len(), width * height, "wrong number of texels for the image size");
+
+        // Texels near the poles cover less of the sphere (proportional to $\sin\theta$
+        // at their center), so send less light into the scene.
+        let weights: Vec<Float> = texels.iter().enumerate()
+            .map(|(i, texel)| {
+                let theta = ((i / width) as Float + 0.5) / height as Float * Float::get_pi();
+                Float::max(0.0, luminance(texel)) * theta.sin()
+            })
+            .collect();
+        let distribution = PiecewiseConstant2D::new(&weights, width);
+
+        Self {
+            width,
+            height,
+            texels,
+            rotation,
+            strength,
+            distribution,
+        }
+    }
+
+    /// Reads an image file; see `DecodedImage::load()`.
+    pub fn load(filename: &str, rotation: Float, strength: Float) -> Result<Self, String> {
+        let image = DecodedImage::load(filename, None)?;
+        Ok(Self::new(image.width, image.height, image.texels, rotation, strength))
+    }
+
+    /// Where in $[0, 1]^2$ (across and down the image) the map shows `direction`.
+    fn direction_to_image(&self, direction: &Vec3) -> (Float, Float) {
+        let direction = direction.clone().normalize();
+        let theta = Float::acos(Float::clamp(direction.y(), -1.0, 1.0));
+        let phi = Float::atan2(direction.z(), direction.x()) - self.rotation;
+        let two_pi = 2.0 * Float::get_pi();
+
+        (phi.rem_euclid(two_pi) / two_pi, theta / Float::get_pi())
+    }
+
+    /// The inverse of `direction_to_image()`.
+    fn image_to_direction(&self, s: Float, t: Float) -> Vec3 {
+        let theta = t * Float::get_pi();
+        let phi = s * 2.0 * Float::get_pi() + self.rotation;
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+
+        Vec3::new(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi)
+    }
+
+    /// Converts a density over the image to one over solid angle. The texels near a 
+    /// point $(s, t)$ cover $2\pi^2 \sin\theta$ times as much of the sphere as of the 
+    /// unit square.
+    fn image_pdf_to_solid_angle(t: Float, pdf: Float) -> Float {
+        let sin_theta = Float::sin(t * Float::get_pi());
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+
+        pdf / (2.0 * Float::get_pi() * Float::get_pi() * sin_theta)
+    }
+
+    fn texel(&self, x: i64, y: i64) -> &Spectrum {
+        // Around the horizon the image wraps, but not over the poles.
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.clamp(0, self.height as i64 - 1) as usize;
+        &self.texels[y * self.width + x]
+    }
+}
+
+impl BackgroundLike for EnvironmentMap {
+    fn radiance(&self, direction: &Vec3) -> Spectrum {
+        let (s, t) = self.direction_to_image(direction);
+
+        // Bilinear interpolation between the texel centers around the lookup.
+        let x = s * self.width as Float - 0.5;
+        let y = t * self.height as Float - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (dx, dy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let color = ((1.0 - dx) * (1.0 - dy)) * self.texel(x0, y0)
+            + (dx * (1.0 - dy)) * self.texel(x0 + 1, y0)
+            + ((1.0 - dx) * dy) * self.texel(x0, y0 + 1)
+            + (dx * dy) * self.texel(x0 + 1, y0 + 1);
+
+        self.strength * color
+    }
+
+    fn is_sampled_as_light(&self) -> bool {
+        true
+    }
+
+    fn sample_direction(&self, rng: &mut RandomNumberGenerator) -> (Vec3, Float) {
+        let sample = self.distribution.sample(rng);
+        let (s, t) = (sample.point.x(), sample.point.y());
+
+        (self.image_to_direction(s, t), Self::image_pdf_to_solid_angle(t, sample.pdf))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> Float {
+        let (s, t) = self.direction_to_image(direction);
+        Self::image_pdf_to_solid_angle(t, self.distribution.pdf(s, t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions_map_to_the_layout() {
+        // Left half red, right half blue, with a single row.
+        let red = Spectrum::new(1.0, 0.0, 0.0);
+        let blue = Spectrum::new(0.0, 0.0, 1.0);
+        let map = EnvironmentMap::new(2, 1, vec![red, blue], 0.0, 2.0);
+
+        // A quarter of the way across is the middle of the left half.
+        let along_z = map.radiance(&Vec3::new(0.0, 0.3, 1.0));
+        assert!(Vec3::are_equal(&along_z, &Spectrum::new(2.0, 0.0, 0.0)));
+        let along_minus_z = map.radiance(&Vec3::new(0.0, -0.3, -1.0));
+        assert!(Vec3::are_equal(&along_minus_z, &Spectrum::new(0.0, 0.0, 2.0)));
+
+        // Turning the map a quarter turn brings what was along $+z$ to $-x$.
+        let turned = EnvironmentMap::new(2, 1, map.texels.clone(), 0.5 * Float::get_pi(), 1.0);
+        let along_minus_x = turned.radiance(&Vec3::new(-1.0, 0.0, 0.0));
+        assert!(Vec3::are_equal(&along_minus_x, &Spectrum::new(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn sampling_finds_the_bright_spot() {
+        // A dim sky with one bright texel.
+        let (width, height) = (16, 8);
+        let mut texels = vec![Spectrum::new(0.1, 0.1, 0.1); width * height];
+        texels[2 * width + 5] = Spectrum::new(1000.0, 1000.0, 1000.0);
+        let map = EnvironmentMap::new(width, height, texels, 0.3, 1.0);
+        let mut rng = RandomNumberGenerator::from_seed(1);
+
+        // Estimate the light arriving from all directions, which is the sum of the 
+        // texels weighed by the solid angle they cover.
+        let num_samples = 10_000;
+        let mut estimate = 0.0;
+        let mut num_in_bright_texel = 0;
+        for _ in 0..num_samples {
+            let (direction, pdf) = map.sample_direction(&mut rng);
+            assert!((pdf - map.pdf(&direction)).abs() <= 1e-3 * pdf);
+            let (s, t) = map.direction_to_image(&direction);
+            if (s * width as Float) as usize == 5 && (t * height as Float) as usize == 2 {
+                num_in_bright_texel += 1;
+            }
+            estimate += map.texel((s * width as Float) as i64, (t * height as Float) as i64).x() / pdf;
+        }
+        estimate /= num_samples as Float;
+
+        let expected: Float = (0..height)
+            .map(|y| {
+                let (t0, t1) = (y as Float / height as Float, (y + 1) as Float / height as Float);
+                let solid_angle = 2.0 * Float::get_pi() / width as Float
+                    * (Float::cos(t0 * Float::get_pi()) - Float::cos(t1 * Float::get_pi()));
+                (0..width).map(|x| map.texel(x as i64, y as i64).x() * solid_angle).sum::<Float>()
+            })
+            .sum();
+        assert!(num_in_bright_texel > num_samples * 9 / 10);
+        assert!((estimate - expected).abs() < 0.02 * expected);
+    }
+}
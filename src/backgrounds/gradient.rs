@@ -0,0 +1,31 @@
+use crate::{utility::math::vector::Vec3, light::Spectrum};
+use super::traits::BackgroundLike;
+
+/// A simple sky, blending from `horizon` straight ahead to `zenith` straight up (and to 
+/// `ground` straight down), linearly in the height of the direction.
+pub struct GradientBackground {
+    zenith: Spectrum,
+    horizon: Spectrum,
+    ground: Spectrum,
+}
+
+impl GradientBackground {
+    pub fn new(zenith: Spectrum, horizon: Spectrum, ground: Spectrum) -> Self {
+        Self {
+            zenith,
+            horizon,
+            ground,
+        }
+    }
+}
+
+impl BackgroundLike for GradientBackground {
+    fn radiance(&self, direction: &Vec3) -> Spectrum {
+        let height = direction.clone().normalize().y();
+        if height >= 0.0 {
+            (1.0 - height) * &self.horizon + height * &self.zenith
+        } else {
+            (1.0 + height) * &self.horizon - height * &self.ground
+        }
+    }
+}
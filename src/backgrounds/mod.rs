@@ -0,0 +1,8 @@
+//! What rays that leave the scene without hitting anything see. This is how light from 
+//! far away, like the sky, enters the scene.
+
+pub mod traits;
+pub mod constant;
+pub mod gradient;
+pub mod environment_map;
+pub mod sky;
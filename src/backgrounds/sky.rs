@@ -0,0 +1,326 @@
+//! The daylight sky model of Preetham, Shirley and Smits, "A Practical Analytic Model
+//! for Daylight" (1999). The sky's luminance and chromaticity are fitted by Perez et
+//! al.'s formula, whose coefficients depend on the turbidity (how hazy the air is) and
+//! the height of the sun.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::{
+        math::{
+            float::{Float, FloatConstants},
+            vector::{Vec3, dot},
+            orthonormal_basis::OrthonormalBasis
+        },
+        post_process::luminance,
+        rng::RandomNumberGenerator
+    },
+    light::Spectrum,
+    sampler
+};
+use super::traits::BackgroundLike;
+
+// E==== IMPORTS }}}1
+
+/// The model gives luminance in kcd/m², which we scale so that a clear sky at midday
+/// is around 1.
+const LUMINANCE_SCALE: Float = 0.05;
+
+/// The luminance of the sun outside the atmosphere, in kcd/m².
+const SUN_LUMINANCE: Float = 1.6e6;
+
+/// The range of turbidities the model was fitted to.
+pub const MIN_TURBIDITY: Float = 1.7;
+pub const MAX_TURBIDITY: Float = 10.0;
+
+/// How far (in radians) the sun sinks below the horizon while the sky fades to black, 
+/// about the length of civil twilight.
+const TWILIGHT_DEPRESSION: Float = 0.1;
+
+/// Half of the angle the sun covers as seen from the earth.
+const SUN_ANGULAR_RADIUS: Float = 0.00465;
+
+/// The wavelengths (in micrometers) at which we attenuate the sunlight for the red,
+/// green and blue channels.
+const WAVELENGTHS: [Float; 3] = [0.65, 0.55, 0.45];
+
+/// How finely the sky is divided when integrating the light it sends to the ground.
+const NUM_THETA_STEPS: usize = 32;
+const NUM_PHI_STEPS: usize = 64;
+
+/// A sky lit by the sun, above a diffuse ground lit by both.
+pub struct SkyBackground {
+    /// Points towards the sun, normalized.
+    sun_direction: Vec3,
+    /// For the luminance $Y$ and the chromaticity coordinates $x$ and $y$, the Perez
+    /// coefficients $A, \ldots, E$.
+    perez: [[Float; 5]; 3],
+    /// $Y$, $x$ and $y$ straight up, divided by the Perez function there so that the
+    /// model can be evaluated by just multiplying the Perez function by this.
+    zenith: [Float; 3],
+    /// The sunlight arriving at the ground, or black if the sun is below the horizon.
+    sun_radiance: Spectrum,
+    /// The cosine of the sun's angular radius.
+    cos_sun_radius: Float,
+    /// What is seen below the horizon.
+    ground: Spectrum,
+    /// How often `sample_direction()` aims at the sun rather than anywhere.
+    sun_probability: Float,
+}
+
+impl SkyBackground {
+    /// `turbidity` ranges from 2 for very clear air to about 10 for haze, and is clamped
+    /// to the range the model was fitted to. Everything the sky gives off is multiplied
+    /// by `strength`.
+    pub fn new(sun_direction: Vec3, turbidity: Float, ground_albedo: Spectrum, strength: Float) -> Self {
+        let sun_direction = sun_direction.normalize();
+        let t = Float::clamp(turbidity, MIN_TURBIDITY, MAX_TURBIDITY);
+        // The model is only valid for the sun above the horizon. Below it, we keep the 
+        // sky of the sunset, and fade it out as the sun sinks further.
+        let theta_sun = Float::min(Float::acos(Float::clamp(sun_direction.y(), -1.0, 1.0)), 0.5 * Float::get_pi());
+        let twilight = {
+            let depression = -Float::asin(Float::clamp(sun_direction.y(), -1.0, 1.0));
+            let s = Float::clamp(1.0 - depression / TWILIGHT_DEPRESSION, 0.0, 1.0);
+            s * s * (3.0 - 2.0 * s)
+        };
+
+        let perez = [
+            [0.1787 * t - 1.4630, -0.3554 * t + 0.4275, -0.0227 * t + 5.3251, 0.1206 * t - 2.5771, -0.0670 * t + 0.3703],
+            [-0.0193 * t - 0.2592, -0.0665 * t + 0.0008, -0.0004 * t + 0.2125, -0.0641 * t - 0.8989, -0.0033 * t + 0.0452],
+            [-0.0167 * t - 0.2608, -0.0950 * t + 0.0092, -0.0079 * t + 0.2102, -0.0441 * t - 1.6537, -0.0109 * t + 0.0529],
+        ];
+
+        let zenith = {
+            let chi = (4.0 / 9.0 - t / 120.0) * (Float::get_pi() - 2.0 * theta_sun);
+            let luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+            let chromaticity = |m: [[Float; 4]; 3]| {
+                let thetas = [theta_sun.powi(3), theta_sun.powi(2), theta_sun, 1.0];
+                let ts = [t * t, t, 1.0];
+                (0..3).map(|i| ts[i] * (0..4).map(|j| m[i][j] * thetas[j]).sum::<Float>()).sum::<Float>()
+            };
+            let x = chromaticity([
+                [0.00166, -0.00375, 0.00209, 0.0],
+                [-0.02903, 0.06377, -0.03202, 0.00394],
+                [0.11693, -0.21196, 0.06052, 0.25886],
+            ]);
+            let y = chromaticity([
+                [0.00275, -0.00610, 0.00317, 0.0],
+                [-0.04214, 0.08970, -0.04153, 0.00516],
+                [0.15346, -0.26756, 0.06670, 0.26688],
+            ]);
+
+            let values = [Float::max(0.0, luminance) * LUMINANCE_SCALE * strength * twilight, x, y];
+            [0, 1, 2].map(|i| values[i] / perez_function(&perez[i], 1.0, theta_sun.cos()))
+        };
+
+        let sun_radiance = if sun_direction.y() > 0.0 {
+            (SUN_LUMINANCE * LUMINANCE_SCALE * strength) * sun_transmittance(theta_sun, t)
+        } else {
+            Spectrum::new(0.0, 0.0, 0.0)
+        };
+
+        let mut sky = Self {
+            sun_direction,
+            perez,
+            zenith,
+            sun_radiance,
+            cos_sun_radius: SUN_ANGULAR_RADIUS.cos(),
+            ground: Spectrum::new(0.0, 0.0, 0.0),
+            sun_probability: 0.0,
+        };
+
+        // The ground reflects (diffusely) the light of the sky and the sun.
+        let sun_solid_angle = 2.0 * Float::get_pi() * (1.0 - sky.cos_sun_radius);
+        let (sky_irradiance, sky_power) = sky.integrate_sky();
+        let irradiance = sky_irradiance + (sun_solid_angle * sky.sun_direction.y().max(0.0)) * &sky.sun_radiance;
+        sky.ground = (Float::get_1_pi() * &irradiance) * ground_albedo;
+
+        // Aim at the sun in proportion to how much of the light comes from it, but
+        // always leave room for the rest of the sky.
+        let sun_power = sun_solid_angle * luminance(&sky.sun_radiance);
+        let total_power = sun_power + sky_power + 2.0 * Float::get_pi() * luminance(&sky.ground);
+        if sun_power > 0.0 {
+            sky.sun_probability = Float::clamp(sun_power / total_power, 0.1, 0.9);
+        }
+
+        sky
+    }
+
+    /// The sky above the horizon, without the sun.
+    fn sky_radiance(&self, direction: &Vec3) -> Spectrum {
+        let cos_theta = Float::max(direction.y(), 1e-3);
+        let cos_gamma = Float::clamp(dot(direction, &self.sun_direction), -1.0, 1.0);
+
+        let [luminance, x, y] = [0, 1, 2]
+            .map(|i| self.zenith[i] * perez_function(&self.perez[i], cos_theta, cos_gamma));
+        xyy_to_linear_srgb(x, y, luminance)
+    }
+
+    /// The light from the sky arriving at a horizontal surface, and the luminance of
+    /// the sky integrated over all directions above the horizon (both without the sun).
+    fn integrate_sky(&self) -> (Spectrum, Float) {
+        let d_theta = 0.5 * Float::get_pi() / NUM_THETA_STEPS as Float;
+        let d_phi = 2.0 * Float::get_pi() / NUM_PHI_STEPS as Float;
+
+        let mut irradiance = Spectrum::new(0.0, 0.0, 0.0);
+        let mut power = 0.0;
+        for i in 0..NUM_THETA_STEPS {
+            let theta = (i as Float + 0.5) * d_theta;
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            for j in 0..NUM_PHI_STEPS {
+                let (sin_phi, cos_phi) = ((j as Float + 0.5) * d_phi).sin_cos();
+                let direction = Vec3::new(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi);
+                let radiance = self.sky_radiance(&direction);
+
+                let solid_angle = sin_theta * d_theta * d_phi;
+                power += solid_angle * luminance(&radiance);
+                irradiance = irradiance + (solid_angle * cos_theta) * radiance;
+            }
+        }
+
+        (irradiance, power)
+    }
+
+    fn sees_sun(&self, direction: &Vec3) -> bool {
+        dot(direction, &self.sun_direction) >= self.cos_sun_radius
+    }
+
+    /// The density of directions towards the sun, sampled uniformly in its disk.
+    fn sun_pdf(&self) -> Float {
+        1.0 / (2.0 * Float::get_pi() * (1.0 - self.cos_sun_radius))
+    }
+}
+
+impl BackgroundLike for SkyBackground {
+    fn radiance(&self, direction: &Vec3) -> Spectrum {
+        let direction = direction.clone().normalize();
+        if direction.y() < 0.0 {
+            return self.ground.clone();
+        }
+
+        let sky = self.sky_radiance(&direction);
+        if self.sees_sun(&direction) {
+            sky + &self.sun_radiance
+        } else {
+            sky
+        }
+    }
+
+    fn is_sampled_as_light(&self) -> bool {
+        true
+    }
+
+    /// Picks between the disk of the sun, and the whole sphere uniformly (the sky is
+    /// smooth enough for that).
+    fn sample_direction(&self, rng: &mut RandomNumberGenerator) -> (Vec3, Float) {
+        let direction = if rng.next_float() < self.sun_probability {
+            let local = sampler::uniform_in_2sphere_cone(rng, self.cos_sun_radius).point;
+            OrthonormalBasis::new_from_vector(&self.sun_direction).vector_from_local(local)
+        } else {
+            sampler::uniform_on_2sphere(rng).point
+        };
+        let pdf = self.pdf(&direction);
+
+        (direction, pdf)
+    }
+
+    fn pdf(&self, direction: &Vec3) -> Float {
+        let direction = direction.clone().normalize();
+        let sun_pdf = if self.sees_sun(&direction) { self.sun_pdf() } else { 0.0 };
+
+        self.sun_probability * sun_pdf + (1.0 - self.sun_probability) * 0.25 * Float::get_1_pi()
+    }
+}
+
+/// Perez et al.'s formula for the distribution of light over the sky, at zenith angle
+/// $\theta$ and angle $\gamma$ from the sun.
+fn perez_function(coefficients: &[Float; 5], cos_theta: Float, cos_gamma: Float) -> Float {
+    let [a, b, c, d, e] = *coefficients;
+    let gamma = cos_gamma.acos();
+
+    (1.0 + a * Float::exp(b / cos_theta)) * (1.0 + c * Float::exp(d * gamma) + e * cos_gamma * cos_gamma)
+}
+
+fn xyy_to_linear_srgb(x: Float, y: Float, luminance: Float) -> Spectrum {
+    if y <= 0.0 {
+        return Spectrum::new(0.0, 0.0, 0.0);
+    }
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+
+    Spectrum::new(
+        Float::max(0.0, 3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z),
+        Float::max(0.0, -0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z),
+        Float::max(0.0, 0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z)
+    )
+}
+
+/// The fraction of sunlight (for each channel) that makes it through the atmosphere
+/// with the sun at zenith angle `theta_sun`, from scattering by molecules (Rayleigh) and
+/// by aerosols (Ångström's formula). Absorption by ozone and water is ignored.
+fn sun_transmittance(theta_sun: Float, turbidity: Float) -> Spectrum {
+    // How much air the light passes through, relative to straight up.
+    let theta_degrees = theta_sun.to_degrees();
+    let relative_mass = 1.0 / (theta_sun.cos() + 0.15 * Float::powf(93.885 - theta_degrees, -1.253));
+    let beta = 0.04608 * turbidity - 0.04586;
+    const ALPHA: Float = 1.3;
+
+    let [r, g, b] = WAVELENGTHS.map(|lambda| {
+        let rayleigh = Float::exp(-0.008735 * lambda.powf(-4.08) * relative_mass);
+        let aerosol = Float::exp(-beta * lambda.powf(-ALPHA) * relative_mass);
+        rayleigh * aerosol
+    });
+
+    Spectrum::new(r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daylight_looks_plausible() {
+        let sun_direction = Vec3::new(0.5, 0.8, 0.3);
+        let sky = SkyBackground::new(sun_direction.clone(), 3.0, Spectrum::new(0.3, 0.3, 0.3), 1.0);
+
+        // Blue overhead, brighter towards the sun, with the sun far brighter still.
+        let up = sky.radiance(&Vec3::new(0.0, 1.0, 0.0));
+        assert!(up.z() > up.x() && luminance(&up) > 0.1 && luminance(&up) < 10.0);
+        let near_sun = sky.radiance(&Vec3::new(0.55, 0.8, 0.3));
+        assert!(luminance(&near_sun) > luminance(&up));
+        let sun = sky.radiance(&sun_direction);
+        assert!(luminance(&sun) > 1000.0 * luminance(&up));
+
+        // Sampling matches the pdf, and finds the sun.
+        let mut rng = RandomNumberGenerator::from_seed(1);
+        let mut num_towards_sun = 0;
+        for _ in 0..1000 {
+            let (direction, pdf) = sky.sample_direction(&mut rng);
+            assert!((pdf - sky.pdf(&direction)).abs() <= 1e-3 * pdf);
+            if sky.sees_sun(&direction) {
+                num_towards_sun += 1;
+            }
+        }
+        assert!(num_towards_sun > 50);
+
+        // At night, only the sky's afterglow (if anything) is left.
+        let night = SkyBackground::new(Vec3::new(0.0, -1.0, 0.0), 3.0, Spectrum::new(0.3, 0.3, 0.3), 1.0);
+        assert_eq!(night.sun_probability, 0.0);
+        assert!(luminance(&night.radiance(&Vec3::new(0.0, -1.0, 0.0))) < luminance(&sky.ground));
+    }
+
+    #[test]
+    fn sky_fades_out_after_sunset() {
+        let zenith_with_sun_at = |degrees: Float| {
+            let (sin, cos) = Float::sin_cos(degrees.to_radians());
+            let sky = SkyBackground::new(Vec3::new(cos, sin, 0.0), 3.0, Spectrum::new(0.3, 0.3, 0.3), 1.0);
+            luminance(&sky.radiance(&Vec3::new(0.0, 1.0, 0.0)))
+        };
+
+        let sunset = zenith_with_sun_at(0.0);
+        let twilight = zenith_with_sun_at(-5.0);
+        assert!(twilight > 0.0 && twilight < sunset);
+        assert!(zenith_with_sun_at(-10.0) < twilight);
+        assert_eq!(zenith_with_sun_at(-90.0), 0.0);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{
+    utility::{math::{vector::Vec3, float::{Float, FloatConstants}}, rng::RandomNumberGenerator},
+    light::Spectrum,
+    sampler
+};
+
+/// Backgrounds are infinitely far away, so what they look like only depends on the 
+/// direction they are seen in. Directions are in world space, with $+y$ up.
+pub trait BackgroundLike: Send + Sync {
+    /// The light arriving along a ray that escapes the scene in `direction`, which need
+    /// not be normalized.
+    fn radiance(&self, direction: &Vec3) -> Spectrum;
+
+    /// Whether integrators should sample the background directly, like the lights in
+    /// the scene. This pays off when most of its light comes from a few directions. 
+    /// Otherwise, paths escaping the scene find it well enough on their own.
+    fn is_sampled_as_light(&self) -> bool {
+        false
+    }
+
+    /// A (normalized) direction towards the background, and its density with respect 
+    /// to solid angle. By default, directions are uniform over the sphere.
+    fn sample_direction(&self, rng: &mut RandomNumberGenerator) -> (Vec3, Float) {
+        let sample = sampler::uniform_on_2sphere(rng);
+        (sample.point, sample.pdf)
+    }
+
+    /// The density with which `sample_direction()` would have produced `direction`.
+    fn pdf(&self, _direction: &Vec3) -> Float {
+        0.25 * Float::get_1_pi()
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
     utility::{
         math::{
             vector::Vec3, 
-            ray::Ray3,
+            ray::{Ray3, RayDifferentials},
             float::Float, 
             angle::{Angle, AngleUnits}
         }, 
@@ -104,12 +104,12 @@ impl Camera {
         // you get from the focus plane, the larger the difference between the offset
         // ray intersection and a non-offset ray intersection.
 
-        // These are numbers between 0 and 1.
-        let tx = pixel_x / (self.resolution.width as Float);
-        let ty = pixel_y / (self.resolution.height as Float);
-
         let local_ray_origin = self.aperture_radius * sampler::uniform_in_1sphere(rng).point;
-        let local_ray_direction = {
+        let local_ray_direction_through = |pixel_x: Float, pixel_y: Float| {
+            // These are numbers between 0 and 1.
+            let tx = pixel_x / (self.resolution.width as Float);
+            let ty = pixel_y / (self.resolution.height as Float);
+
             let pixel_in_image_plane = 
                 &self.bottom_left_corner_of_image_plane 
                 + Vec3::new(tx * self.viewport_size.width, ty * self.viewport_size.height, 0.0);
@@ -122,7 +122,19 @@ impl Camera {
 
             focus_plane_intersection - &local_ray_origin
         };
-        let local_ray = Ray3::new(local_ray_origin, local_ray_direction);
+
+        // The neighboring rays go through the same point of the lens.
+        let differentials = RayDifferentials {
+            rx_origin: local_ray_origin.clone(),
+            rx_direction: local_ray_direction_through(pixel_x + 1.0, pixel_y),
+            ry_origin: local_ray_origin.clone(),
+            ry_direction: local_ray_direction_through(pixel_x, pixel_y + 1.0),
+        };
+        let mut local_ray = Ray3::new(
+            local_ray_origin.clone(), 
+            local_ray_direction_through(pixel_x, pixel_y)
+        );
+        local_ray.differentials = Some(differentials);
 
         self.transform.ray_to_global(&local_ray)
     }
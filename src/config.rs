@@ -1,10 +1,10 @@
 
 use tracing::{error, warn};
 
-static MIRTH_CONFIG: Config = Config {
+pub static MIRTH_CONFIG: Config = Config {
     acceleration_structure: AccelerationStructure {
         kind: AccStructureKind::BBH,
-        axis_selection_method: AccStructureAxisSelectionMethod::Random,
+        axis_selection_method: AccStructureAxisSelectionMethod::LargestExtent,
     },
 };
 
@@ -15,27 +15,36 @@ pub fn validate_config() {
     ) && !matches!(
         MIRTH_CONFIG.acceleration_structure.axis_selection_method,
         AccStructureAxisSelectionMethod::LargestExtent
+            | AccStructureAxisSelectionMethod::SurfaceAreaHeuristic
     )) {
-        warn!("using 'BBH' acceleration structure with suboptimal axis determination method ('LargestExtent' is the intended method)");
+        warn!("using 'BBH' acceleration structure with suboptimal axis determination method ('LargestExtent' or 'SurfaceAreaHeuristic' are the intended methods)");
     }
 }
 
-struct Config {
-    acceleration_structure: AccelerationStructure,
+pub struct Config {
+    pub acceleration_structure: AccelerationStructure,
 }
 
-struct AccelerationStructure {
-    kind: AccStructureKind,
-    axis_selection_method: AccStructureAxisSelectionMethod,
+pub struct AccelerationStructure {
+    pub kind: AccStructureKind,
+    pub axis_selection_method: AccStructureAxisSelectionMethod,
 }
 
-enum AccStructureKind {
+pub enum AccStructureKind {
     Nothing,
+    /// Bounding box hierarchy
     BBH,
 }
 
-enum AccStructureAxisSelectionMethod {
+/// How a node of the bounding box hierarchy is split into its two children.
+pub enum AccStructureAxisSelectionMethod {
+    /// Split at the median along a randomly chosen axis.
     Random,
+    /// Split at the median, cycling through the axes as we go down the tree.
     Alternating,
+    /// Split at the median along the axis in which the node is longest.
     LargestExtent,
+    /// Split along the axis in which the node is longest, at whichever position
+    /// minimizes the expected cost of intersecting the children.
+    SurfaceAreaHeuristic,
 }
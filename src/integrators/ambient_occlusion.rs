@@ -4,21 +4,21 @@
 use crate::{
     utility::{math::ray::Ray3, rng::RandomNumberGenerator}, 
     light::{Spectrum, ColorConstantsQueryable}, 
-    objects::{object_group::ObjectGroup, object::SampleNewRayInfo}
+    objects::object::SampleNewRayInfo
 };
-use super::traits::IntegratorLike;
+use super::traits::{IntegratorLike, RenderContext};
 
 // E==== IMPORTS }}}1
 
 pub struct AmbientOcclusionIntegrator {}
 
 impl IntegratorLike for AmbientOcclusionIntegrator {
-    fn spectrum_from_ray(&self, object_group: &ObjectGroup, ray: &Ray3, rng: &mut RandomNumberGenerator) -> Spectrum {
+    fn spectrum_from_ray(&self, context: &RenderContext, ray: &Ray3, rng: &mut RandomNumberGenerator) -> Spectrum {
         /* Check if ray intersects any objects */
         
-        let intersection_info = object_group.intersect(ray);
+        let intersection_info = context.objects.intersect(ray);
         if let None = intersection_info.intersected_object {
-            return Spectrum::black();
+            return context.background.radiance(&ray.direction);
         }
 
         let intersected_object = intersection_info.intersected_object.unwrap();
@@ -36,7 +36,7 @@ impl IntegratorLike for AmbientOcclusionIntegrator {
         };
 
         let shadow_ray = sample_result.scattered_ray;
-        let shadow_intersection = object_group.intersect(&shadow_ray);
+        let shadow_intersection = context.objects.intersect(&shadow_ray);
 
         if let Some(_) = shadow_intersection.intersected_object {
             return Spectrum::black();
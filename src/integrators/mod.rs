@@ -1,4 +1,5 @@
 
 pub mod traits;
 pub mod ambient_occlusion;
+pub mod path;
 
@@ -0,0 +1,204 @@
+//! A unidirectional path tracer. Starting from the camera ray, we repeatedly
+//! intersect the scene and let the material of whatever we hit choose the next
+//! direction, until we either escape the scene, hit the recursion limit, or hit
+//! something that doesn't scatter. Light enters the path wherever it passes an
+//! emissive surface, and from the background if it escapes.
+//!
+//! Hitting a small light this way is unlikely, so by default we also sample a light
+//! directly at every bounce ("next event estimation") and check if it is visible.
+//! Both strategies can produce the same path, so their contributions are combined
+//! with multiple importance sampling.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::{
+        math::{ray::Ray3, float::Float, vector::Point3},
+        rng::RandomNumberGenerator
+    },
+    light::{Spectrum, ColorConstantsQueryable},
+    objects::{
+        object::{Object, SampleNewRayInfo},
+        shapes::traits::ShapeIntersectionInfo
+    }
+};
+use super::traits::{IntegratorLike, RenderContext};
+
+// E==== IMPORTS }}}1
+
+/// Shadow rays stop this (relative) amount short of the point sampled on the light, so
+/// that they don't register the light itself as an occluder.
+const SHADOW_RAY_EPSILON: Float = 0.0001;
+
+pub struct PathIntegrator {
+    /// The maximum number of times a path is allowed to bounce off of a surface. Paths
+    /// that exceed this are considered to carry no light.
+    recursion_limit: u32,
+    /// Whether to sample lights directly at each bounce.
+    next_event_estimation: bool,
+}
+
+impl PathIntegrator {
+    pub fn new(recursion_limit: u32, next_event_estimation: bool) -> Self {
+        Self {
+            recursion_limit,
+            next_event_estimation,
+        }
+    }
+
+    /// The light arriving at the intersection point directly from a randomly chosen
+    /// light and scattered back along `ray`, weighed for multiple importance sampling.
+    fn sample_direct_lighting(
+        &self,
+        context: &RenderContext,
+        ray: &Ray3,
+        object: &Object,
+        shape_intersection: &ShapeIntersectionInfo,
+        rng: &mut RandomNumberGenerator
+    ) -> Spectrum {
+        let light = match context.lights.choose(rng) {
+            Some(light) => light,
+            None => { return Spectrum::black(); }
+        };
+
+        let light_sample = light.sample_from(&shape_intersection.point, rng);
+        let light_pdf = light_sample.pdf * context.lights.selection_pdf();
+        if light_pdf <= 0.0 {
+            return Spectrum::black();
+        }
+
+        let shadow_ray = {
+            let mut shadow_ray = Ray3::new_from_surface(
+                &shape_intersection.point, 
+                &shape_intersection.geometric_normal, 
+                light_sample.direction.clone()
+            );
+            shadow_ray.max_t = light_sample.distance * (1.0 - SHADOW_RAY_EPSILON);
+            shadow_ray
+        };
+
+        let material_pdf = object.material_pdf(ray, shape_intersection, &light_sample.direction);
+        if material_pdf <= 0.0 {
+            return Spectrum::black();
+        }
+
+        if context.objects.intersect(&shadow_ray).intersected_object.is_some() {
+            return Spectrum::black();
+        }
+
+        let weight = if light.is_delta() {
+            1.0
+        } else {
+            power_heuristic(light_pdf, material_pdf)
+        };
+        let albedo = object.albedo_at(ray, shape_intersection);
+        let bsdf = object.eval_material(ray, shape_intersection, &light_sample.direction);
+
+        (weight / light_pdf) * (albedo * bsdf * light_sample.radiance)
+    }
+}
+
+impl IntegratorLike for PathIntegrator {
+    fn spectrum_from_ray(&self, context: &RenderContext, ray: &Ray3, rng: &mut RandomNumberGenerator) -> Spectrum {
+        let mut spectrum = Spectrum::black();
+        // The fraction of light arriving along `ray` that makes it back to the camera.
+        let mut throughput = Spectrum::white();
+        let mut ray = ray.clone();
+        // Where `ray` was scattered from, and the pdf it was sampled with. `None` for
+        // the camera ray and for rays leaving specular surfaces.
+        let mut previous_scatter: Option<(Point3, Float)> = None;
+
+        for _ in 0..self.recursion_limit {
+            let intersection_info = context.objects.intersect(&ray);
+            let intersected_object = match intersection_info.intersected_object {
+                Some(object) => object,
+                None => {
+                    let background = context.background;
+                    let weight = match &previous_scatter {
+                        Some((_, scatter_pdf))
+                            if self.next_event_estimation && background.is_sampled_as_light() =>
+                        {
+                            let light_pdf = background.pdf(&ray.direction) * context.lights.selection_pdf();
+                            power_heuristic(*scatter_pdf, light_pdf)
+                        },
+                        _ => 1.0,
+                    };
+                    spectrum = spectrum + weight * (&throughput * &background.radiance(&ray.direction));
+                    break;
+                }
+            };
+            let shape_intersection = &intersection_info.shape_intersection_info;
+
+            /* Light given off by the surface we hit */
+
+            let emitted = intersected_object.emitted(&ray, shape_intersection);
+            let emission_weight = match &previous_scatter {
+                Some((previous_point, scatter_pdf))
+                    if self.next_event_estimation && intersected_object.is_emissive() =>
+                {
+                    let light_pdf = intersected_object.shape_pdf_from(previous_point, &ray.direction)
+                        * context.lights.selection_pdf();
+                    power_heuristic(*scatter_pdf, light_pdf)
+                },
+                _ => 1.0,
+            };
+            spectrum = spectrum + emission_weight * (&throughput * &emitted);
+
+            /* Light arriving directly from a light */
+
+            if self.next_event_estimation {
+                let direct = self.sample_direct_lighting(
+                    context,
+                    &ray,
+                    &intersected_object,
+                    shape_intersection,
+                    rng
+                );
+                spectrum = spectrum + &throughput * &direct;
+            }
+
+            /* Let the material decide where the path goes next */
+
+            let sample_result = {
+                let info = SampleNewRayInfo {
+                    incoming_ray: &ray,
+                    shape_intersection,
+                    rng,
+                };
+
+                intersected_object.sample_new_ray(info)
+            };
+
+            if !sample_result.did_scatter || (!sample_result.is_specular && sample_result.pdf <= 0.0) {
+                break;
+            }
+
+            let albedo = intersected_object.albedo_at(&ray, shape_intersection);
+            throughput = throughput * albedo * sample_result.weight;
+
+            previous_scatter = if sample_result.is_specular {
+                // Light sampling can't produce this path, so there is nothing to weigh
+                // the emission at the next hit against.
+                None
+            } else {
+                Some((shape_intersection.point.clone(), sample_result.pdf))
+            };
+            ray = sample_result.scattered_ray;
+        }
+
+        spectrum
+    }
+}
+
+/// Veach's power heuristic (with exponent 2) for weighing a sample drawn with density
+/// `pdf` against another strategy that would have drawn it with density `other_pdf`.
+fn power_heuristic(pdf: Float, other_pdf: Float) -> Float {
+    let a = pdf * pdf;
+    let b = other_pdf * other_pdf;
+
+    if a + b == 0.0 {
+        return 0.0;
+    }
+
+    a / (a + b)
+}
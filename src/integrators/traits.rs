@@ -2,11 +2,19 @@
 use crate::{
     utility::{math::ray::Ray3, rng::RandomNumberGenerator}, 
     light::Spectrum, 
-    objects::object_group::ObjectGroup
+    objects::object_group::ObjectGroup,
+    lights::light_list::LightList,
+    backgrounds::traits::BackgroundLike
 };
 
-
-pub trait IntegratorLike {
-    fn spectrum_from_ray(&self, object_group: &ObjectGroup, ray: &Ray3, rng: &mut RandomNumberGenerator) -> Spectrum;
+/// Everything about the scene that an integrator may need to query.
+pub struct RenderContext<'a> {
+    pub objects: &'a ObjectGroup,
+    pub lights: &'a LightList,
+    /// What rays that escape the scene see.
+    pub background: &'a dyn BackgroundLike,
 }
 
+pub trait IntegratorLike: Send + Sync {
+    fn spectrum_from_ray(&self, context: &RenderContext, ray: &Ray3, rng: &mut RandomNumberGenerator) -> Spectrum;
+}
@@ -0,0 +1,64 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    utility::{
+        math::{vector::{Point3, Vec3}, float::Float, ray::Ray3},
+        rng::RandomNumberGenerator
+    },
+    objects::{object::Object, shapes::traits::{ShapeIntersectionInfo, is_front_face}}
+};
+use super::traits::{LightLike, LightSampleResult};
+
+// E==== IMPORTS }}}1
+
+/// An object with an emissive material, viewed as a light.
+pub struct AreaLight {
+    object: Arc<Object>,
+}
+
+impl AreaLight {
+    pub fn new(object: Arc<Object>) -> Self {
+        Self {
+            object,
+        }
+    }
+}
+
+impl LightLike for AreaLight {
+    fn sample_from(&self, reference: &Point3, rng: &mut RandomNumberGenerator) -> LightSampleResult {
+        let shape_sample = self.object.sample_shape_from(reference, rng);
+
+        let to_light = &shape_sample.point - reference;
+        let distance = to_light.length();
+        let direction = to_light / distance;
+
+        // Query the emission as if a ray from `reference` had hit the sampled point.
+        let radiance = {
+            let ray = Ray3::new(reference.clone(), direction.clone());
+            let shape_intersection = ShapeIntersectionInfo {
+                did_hit: true,
+                point: shape_sample.point,
+                t: distance,
+                is_front_face: is_front_face(&direction, &shape_sample.surface_normal),
+                geometric_normal: shape_sample.surface_normal.clone(),
+                shading_normal: shape_sample.surface_normal,
+                ..Default::default()
+            };
+
+            self.object.emitted(&ray, &shape_intersection)
+        };
+
+        LightSampleResult {
+            direction,
+            distance,
+            radiance,
+            pdf: shape_sample.pdf,
+        }
+    }
+
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        self.object.shape_pdf_from(reference, direction)
+    }
+}
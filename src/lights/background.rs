@@ -0,0 +1,44 @@
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    utility::{
+        math::{vector::{Point3, Vec3}, float::Float},
+        rng::RandomNumberGenerator
+    },
+    backgrounds::traits::BackgroundLike
+};
+use super::traits::{LightLike, LightSampleResult};
+
+// E==== IMPORTS }}}1
+
+/// The background, viewed as a light infinitely far away in every direction. 
+pub struct BackgroundLight {
+    background: Arc<dyn BackgroundLike>,
+}
+
+impl BackgroundLight {
+    pub fn new(background: Arc<dyn BackgroundLike>) -> Self {
+        Self {
+            background,
+        }
+    }
+}
+
+impl LightLike for BackgroundLight {
+    fn sample_from(&self, _reference: &Point3, rng: &mut RandomNumberGenerator) -> LightSampleResult {
+        let (direction, pdf) = self.background.sample_direction(rng);
+        let radiance = self.background.radiance(&direction);
+
+        LightSampleResult {
+            direction,
+            distance: Float::INFINITY,
+            radiance,
+            pdf,
+        }
+    }
+
+    fn pdf_from(&self, _reference: &Point3, direction: &Vec3) -> Float {
+        self.background.pdf(direction)
+    }
+}
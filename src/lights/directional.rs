@@ -0,0 +1,50 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::{
+        math::{vector::{Point3, Vec3}, float::Float},
+        rng::RandomNumberGenerator
+    },
+    light::Spectrum
+};
+use super::traits::{LightLike, LightSampleResult};
+
+// E==== IMPORTS }}}1
+
+/// Light arriving from infinitely far away along a single direction, like sunlight 
+/// without the size of the sun.
+pub struct DirectionalLight {
+    /// Points back towards the light, normalized.
+    to_light: Vec3,
+    /// The power per unit area arriving at a surface facing the light.
+    irradiance: Spectrum,
+}
+
+impl DirectionalLight {
+    /// `direction` is the way the light travels.
+    pub fn new(direction: Vec3, irradiance: Spectrum) -> Self {
+        Self {
+            to_light: -1.0 * direction.normalize(),
+            irradiance,
+        }
+    }
+}
+
+impl LightLike for DirectionalLight {
+    fn sample_from(&self, _reference: &Point3, _rng: &mut RandomNumberGenerator) -> LightSampleResult {
+        LightSampleResult {
+            direction: self.to_light.clone(),
+            distance: Float::INFINITY,
+            radiance: self.irradiance.clone(),
+            pdf: 1.0,
+        }
+    }
+
+    fn pdf_from(&self, _reference: &Point3, _direction: &Vec3) -> Float {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,43 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::utility::{math::float::Float, rng::RandomNumberGenerator};
+use super::traits::LightLike;
+
+// E==== IMPORTS }}}1
+
+/// All of the lights in the scene. Integrators pick one of these at a time to sample.
+pub struct LightList {
+    lights: Vec<Arc<dyn LightLike>>,
+}
+
+impl LightList {
+    pub fn new_from_vector(lights: Vec<Arc<dyn LightLike>>) -> Self {
+        Self { lights }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Picks a light uniformly at random, or `None` if there are no lights.
+    pub fn choose(&self, rng: &mut RandomNumberGenerator) -> Option<Arc<dyn LightLike>> {
+        if self.lights.is_empty() {
+            return None;
+        }
+
+        let index = ((rng.next_float() * (self.lights.len() as Float)) as usize)
+            .min(self.lights.len() - 1);
+        Some(self.lights[index].clone())
+    }
+
+    /// The probability with which `choose()` picks any particular light.
+    pub fn selection_pdf(&self) -> Float {
+        if self.lights.is_empty() {
+            return 0.0;
+        }
+
+        1.0 / (self.lights.len() as Float)
+    }
+}
@@ -0,0 +1,13 @@
+//! Lights are the things in the scene that integrators can aim rays at directly. Rather
+//! than waiting for a path to stumble onto an emitter, an integrator can ask a light for 
+//! a direction towards it and check whether anything is in the way.
+//!
+//! Not to be confused with `crate::light`, which is about how we represent light itself.
+
+pub mod traits;
+pub mod area;
+pub mod background;
+pub mod point;
+pub mod spot;
+pub mod directional;
+pub mod light_list;
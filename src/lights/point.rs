@@ -0,0 +1,50 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::{
+        math::{vector::{Point3, Vec3}, float::Float},
+        rng::RandomNumberGenerator
+    },
+    light::Spectrum
+};
+use super::traits::{LightLike, LightSampleResult};
+
+// E==== IMPORTS }}}1
+
+/// Shines equally in all directions from a single point.
+pub struct PointLight {
+    position: Point3,
+    /// The power per unit solid angle sent out in any direction.
+    intensity: Spectrum,
+}
+
+impl PointLight {
+    pub fn new(position: Point3, intensity: Spectrum) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl LightLike for PointLight {
+    fn sample_from(&self, reference: &Point3, _rng: &mut RandomNumberGenerator) -> LightSampleResult {
+        let to_light = &self.position - reference;
+        let distance = to_light.length();
+
+        LightSampleResult {
+            direction: to_light / distance,
+            distance,
+            radiance: (1.0 / (distance * distance)) * &self.intensity,
+            pdf: 1.0,
+        }
+    }
+
+    fn pdf_from(&self, _reference: &Point3, _direction: &Vec3) -> Float {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,110 @@
+// S==== IMPORTS {{{1
+
+use crate::{
+    utility::{
+        math::{vector::{Point3, Vec3, dot}, float::Float},
+        rng::RandomNumberGenerator
+    },
+    light::Spectrum
+};
+use super::traits::{LightLike, LightSampleResult};
+
+// E==== IMPORTS }}}1
+
+/// A point light that only shines within a cone. Towards the edge of the cone the light
+/// fades out smoothly, rather than ending abruptly.
+pub struct SpotLight {
+    position: Point3,
+    /// The axis of the cone, normalized.
+    direction: Vec3,
+    /// The power per unit solid angle sent out along the axis.
+    intensity: Spectrum,
+    /// The cosine of the angle from the axis where the light starts to fade.
+    cos_falloff_start: Float,
+    /// The cosine of the half-angle of the cone, beyond which there is no light.
+    cos_cone_angle: Float,
+}
+
+impl SpotLight {
+    /// `cone_angle` is the half-angle of the cone, and the light fades out over the last
+    /// `falloff_angle` of it (both in radians).
+    pub fn new(position: Point3, direction: Vec3, intensity: Spectrum, cone_angle: Float, falloff_angle: Float) -> Self {
+        let falloff_angle = Float::clamp(falloff_angle, 0.0, cone_angle);
+
+        Self {
+            position,
+            direction: direction.normalize(),
+            intensity,
+            cos_falloff_start: Float::cos(cone_angle - falloff_angle),
+            cos_cone_angle: cone_angle.cos(),
+        }
+    }
+
+    /// How much of the intensity goes out in (the normalized) `direction`.
+    fn falloff(&self, direction: &Vec3) -> Float {
+        let cos_theta = dot(direction, &self.direction);
+        if cos_theta >= self.cos_falloff_start {
+            return 1.0;
+        }
+        if cos_theta <= self.cos_cone_angle {
+            return 0.0;
+        }
+
+        let t = (cos_theta - self.cos_cone_angle) / (self.cos_falloff_start - self.cos_cone_angle);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl LightLike for SpotLight {
+    fn sample_from(&self, reference: &Point3, _rng: &mut RandomNumberGenerator) -> LightSampleResult {
+        let to_light = &self.position - reference;
+        let distance = to_light.length();
+        let direction = to_light / distance;
+        let falloff = self.falloff(&(-1.0 * &direction));
+
+        LightSampleResult {
+            direction,
+            distance,
+            radiance: (falloff / (distance * distance)) * &self.intensity,
+            pdf: 1.0,
+        }
+    }
+
+    fn pdf_from(&self, _reference: &Point3, _direction: &Vec3) -> Float {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_fades_towards_the_edge_of_the_cone() {
+        let light = SpotLight::new(
+            Point3::new(0.0, 2.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Spectrum::new(8.0, 8.0, 8.0),
+            Float::to_radians(30.0),
+            Float::to_radians(10.0)
+        );
+        let mut rng = RandomNumberGenerator::from_seed(1);
+        let irradiance_at = |x: Float, rng: &mut RandomNumberGenerator| {
+            light.sample_from(&Point3::new(x, 0.0, 0.0), rng).radiance.x()
+        };
+
+        // Straight below, the inverse square law.
+        assert!((irradiance_at(0.0, &mut rng) - 2.0).abs() < 1e-5);
+        // 15 degrees out is still fully lit, 25 is fading and 35 is dark.
+        let at_angle = |degrees: Float| 2.0 * Float::tan(Float::to_radians(degrees));
+        let full = irradiance_at(at_angle(15.0), &mut rng);
+        assert!((full - 8.0 / (4.0 + at_angle(15.0).powi(2))).abs() < 1e-5);
+        let fading = irradiance_at(at_angle(25.0), &mut rng);
+        assert!(fading > 0.0 && fading < 8.0 / (4.0 + at_angle(25.0).powi(2)));
+        assert_eq!(irradiance_at(at_angle(35.0), &mut rng), 0.0);
+    }
+}
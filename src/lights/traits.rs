@@ -0,0 +1,39 @@
+use crate::{
+    utility::{
+        math::{vector::{Point3, Vec3}, float::Float},
+        rng::RandomNumberGenerator
+    },
+    light::Spectrum
+};
+
+pub struct LightSampleResult {
+    /// The (normalized) direction from the reference point towards the sampled point on 
+    /// the light.
+    pub direction: Vec3,
+    /// How far along `direction` the sampled point is. Anything closer than this 
+    /// blocks the light.
+    pub distance: Float,
+    /// The light arriving at the reference point from the sampled point, assuming 
+    /// nothing is in the way. For delta lights, this is the irradiance at the reference
+    /// point (on a surface facing the light).
+    pub radiance: Spectrum,
+    /// The density with respect to solid angle, as measured from the reference point.
+    /// For delta lights, this is 1.
+    pub pdf: Float,
+}
+
+pub trait LightLike: Send + Sync {
+    /// Samples a direction from `reference` towards the light.
+    fn sample_from(&self, reference: &Point3, rng: &mut RandomNumberGenerator) -> LightSampleResult;
+
+    /// The density, with respect to solid angle, with which `sample_from()` would have 
+    /// produced `direction`.
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float;
+
+    /// Whether the light only shines from a single direction onto any point, like an 
+    /// idealized point light. Rays scattered off of surfaces never hit these, so sampling
+    /// the light is the only way to find them.
+    fn is_delta(&self) -> bool {
+        false
+    }
+}
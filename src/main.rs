@@ -11,8 +11,11 @@ mod scene;
 mod ray_tracer;
 mod sampler;
 mod light;
+mod lights;
+mod backgrounds;
 mod integrators;
 mod scene_parsing;
+mod output;
 
 struct InternalState {
     tracing_subscriber: Box<dyn tracing::Subscriber>,
@@ -24,16 +27,20 @@ fn main() {
 
     let args: Vec<String> = env::args().collect();
 
-    let scene_file = {
-        let filename = args.get(1);
-        if filename.is_none() {
-            error!("no filename specified (as the 1st argument)");
+    let arguments = match parse_arguments(&args[1..]) {
+        Ok(arguments) => arguments,
+        Err(msg) => {
+            error!("{}", msg);
             panic!();
         }
+    };
+
+    let scene_file = {
+        let filename = &arguments.scene_filename;
         
-        let file_string = read_to_string(filename.unwrap());
+        let file_string = read_to_string(filename);
         if file_string.is_err() {
-            error!("could not open file '{}'", filename.unwrap());
+            error!("could not open file '{}'", filename);
             panic!();
         }
 
@@ -57,7 +64,69 @@ fn main() {
     };
     info!("finished parsing scene");
 
-    scene.ray_trace();
+    if let Some(num_threads) = arguments.num_threads {
+        scene.set_num_threads(num_threads);
+    }
+    if let Some(output_path) = arguments.output_path {
+        scene.output_mut().set_path(output_path);
+    }
+
+    utility::interrupt::install_handler();
+    let image = scene.ray_trace();
+    info!("finished rendering");
+
+    if let Err(msg) = scene.output().write(&image) {
+        error!("could not write image to '{}': {}", scene.output().path(), msg);
+        panic!();
+    }
+    info!("wrote image to '{}'", scene.output().path());
+}
+
+/// What was asked for on the command line, which looks like
+///
+/// ```text
+/// mirth <scene file> [--threads <count>] [--output <image file>]
+/// ```
+struct CommandLineArguments {
+    scene_filename: String,
+    /// `None` to use as many threads as the machine can run in parallel.
+    num_threads: Option<usize>,
+    /// Overrides the path in the scene's "output" section.
+    output_path: Option<String>,
+}
+
+/// Parses the command line arguments, not including the name of the program.
+fn parse_arguments(args: &[String]) -> Result<CommandLineArguments, String> {
+    let mut scene_filename: Option<String> = None;
+    let mut num_threads: Option<usize> = None;
+    let mut output_path: Option<String> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threads" => {
+                let count = args.next()
+                    .ok_or("'--threads' must be followed by the number of threads")?;
+                match count.parse::<usize>() {
+                    Ok(count) if count > 0 => { num_threads = Some(count); },
+                    _ => { return Err(format!("invalid number of threads '{}'", count)); },
+                }
+            },
+            "--output" => {
+                let path = args.next()
+                    .ok_or("'--output' must be followed by the path of the image file")?;
+                output_path = Some(path.clone());
+            },
+            _ if scene_filename.is_none() => { scene_filename = Some(arg.clone()); },
+            _ => { return Err(format!("unexpected argument '{}'", arg)); },
+        }
+    }
+
+    Ok(CommandLineArguments {
+        scene_filename: scene_filename.ok_or("no filename specified (as the 1st argument)")?,
+        num_threads,
+        output_path,
+    })
 }
 
 fn initialize_internal_state() {
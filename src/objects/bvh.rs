@@ -0,0 +1,362 @@
+//! A bounding volume hierarchy (the 'BBH' of `config.rs`): a binary tree of boxes, each
+//! containing the boxes of its children, with the primitives stored at the leaves. A ray
+//! then only needs to be tested against the primitives in leaves whose boxes it passes
+//! through.
+//!
+//! The hierarchy only knows about the bounding boxes of its primitives, which it refers
+//! to by their index in the list it was built from. This way it can be built over
+//! anything that can be bounded.
+
+// S==== IMPORTS {{{1
+
+use std::cmp::Ordering;
+use crate::{
+    config::AccStructureAxisSelectionMethod,
+    utility::{
+        math::{aabb::{Aabb, inverse_direction_of}, float::Float, ray::Ray3, vector::Point3},
+        rng::RandomNumberGenerator
+    }
+};
+
+// E==== IMPORTS }}}1
+
+/// Nodes with at most this many primitives are not split any further.
+const MAX_PRIMITIVES_IN_LEAF: usize = 4;
+/// The number of candidate split positions considered by the surface area heuristic.
+const NUM_SAH_BUCKETS: usize = 12;
+
+enum BvhNode {
+    Interior {
+        bounds: Aabb,
+        /// Indices of the children in `Bvh::nodes`.
+        children: [usize; 2],
+        /// The axis along which the primitives were split. The first child holds the
+        /// primitives with the smaller centroids along it.
+        axis: usize,
+    },
+    Leaf {
+        bounds: Aabb,
+        /// The leaf's primitives are `Bvh::primitive_indices[first..first + count]`.
+        first: usize,
+        count: usize,
+    },
+}
+
+pub struct Bvh {
+    /// The root, if any, is at index 0.
+    nodes: Vec<BvhNode>,
+    /// Indices of the primitives, ordered so that each leaf refers to a contiguous range.
+    primitive_indices: Vec<usize>,
+}
+
+/// What we need to know about a primitive while building the hierarchy.
+struct BuildPrimitive {
+    index: usize,
+    bounds: Aabb,
+    centroid: Point3,
+}
+
+// S==== CONSTRUCTION {{{1
+
+impl Bvh {
+    /// Builds a hierarchy over primitives with the given bounding boxes, splitting nodes
+    /// according to `method`.
+    pub fn new(primitive_bounds: &[Aabb], method: &AccStructureAxisSelectionMethod) -> Self {
+        let mut primitives: Vec<BuildPrimitive> = primitive_bounds.iter()
+            .enumerate()
+            .map(|(index, bounds)| BuildPrimitive {
+                index,
+                bounds: bounds.clone(),
+                centroid: bounds.centroid(),
+            })
+            .collect();
+
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            primitive_indices: Vec::with_capacity(primitives.len()),
+        };
+
+        if !primitives.is_empty() {
+            // Only used by `AccStructureAxisSelectionMethod::Random`. We use a fixed seed
+            // so that the hierarchy is the same from run to run.
+            let mut rng = RandomNumberGenerator::from_seed(1);
+            bvh.build_node(&mut primitives, 0, method, &mut rng);
+        }
+
+        bvh
+    }
+
+    /// Builds the subtree over `primitives`, returning the index of its root node.
+    fn build_node(
+        &mut self,
+        primitives: &mut [BuildPrimitive],
+        depth: usize,
+        method: &AccStructureAxisSelectionMethod,
+        rng: &mut RandomNumberGenerator
+    ) -> usize {
+        let bounds = primitives.iter()
+            .fold(Aabb::empty(), |bounds, primitive| bounds.union(&primitive.bounds));
+
+        if primitives.len() <= MAX_PRIMITIVES_IN_LEAF {
+            return self.push_leaf(bounds, primitives);
+        }
+
+        let centroid_bounds = primitives.iter()
+            .fold(Aabb::empty(), |bounds, primitive| bounds.union_point(&primitive.centroid));
+
+        let axis = match method {
+            AccStructureAxisSelectionMethod::Random => {
+                usize::min((rng.next_float() * 3.0) as usize, 2)
+            },
+            AccStructureAxisSelectionMethod::Alternating => depth % 3,
+            AccStructureAxisSelectionMethod::LargestExtent
+                | AccStructureAxisSelectionMethod::SurfaceAreaHeuristic => {
+                centroid_bounds.longest_axis()
+            },
+        };
+
+        let mid = match method {
+            AccStructureAxisSelectionMethod::SurfaceAreaHeuristic => {
+                surface_area_heuristic_split(primitives, &centroid_bounds, axis)
+                    .unwrap_or_else(|| median_split(primitives, axis))
+            },
+            _ => median_split(primitives, axis),
+        };
+
+        // Reserve the spot for this node, so that it comes before its children.
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode::Leaf { bounds: Aabb::empty(), first: 0, count: 0 });
+
+        let (left, right) = primitives.split_at_mut(mid);
+        let left_index = self.build_node(left, depth + 1, method, rng);
+        let right_index = self.build_node(right, depth + 1, method, rng);
+
+        self.nodes[node_index] = BvhNode::Interior {
+            bounds,
+            children: [left_index, right_index],
+            axis,
+        };
+        node_index
+    }
+
+    fn push_leaf(&mut self, bounds: Aabb, primitives: &[BuildPrimitive]) -> usize {
+        let first = self.primitive_indices.len();
+        self.primitive_indices.extend(primitives.iter().map(|primitive| primitive.index));
+
+        self.nodes.push(BvhNode::Leaf {
+            bounds,
+            first,
+            count: primitives.len(),
+        });
+        self.nodes.len() - 1
+    }
+}
+
+/// Partitions `primitives` about the median of their centroids along `axis`, returning
+/// the index of the first primitive in the second half.
+fn median_split(primitives: &mut [BuildPrimitive], axis: usize) -> usize {
+    let mid = primitives.len() / 2;
+    primitives.select_nth_unstable_by(mid, |a, b| {
+        a.centroid.component(axis)
+            .partial_cmp(&b.centroid.component(axis))
+            .unwrap_or(Ordering::Equal)
+    });
+
+    mid
+}
+
+/// The expected cost of intersecting a ray with a node's children is proportional to the
+/// sum, over both children, of the child's surface area times its number of primitives.
+/// We bucket the primitives by centroid along `axis` and split at the bucket boundary
+/// minimizing this cost, returning the index of the first primitive in the second half.
+/// `None` if the primitives can't be separated this way.
+fn surface_area_heuristic_split(
+    primitives: &mut [BuildPrimitive],
+    centroid_bounds: &Aabb,
+    axis: usize
+) -> Option<usize> {
+    let min = centroid_bounds.min.component(axis);
+    let extent = centroid_bounds.diagonal().component(axis);
+    if extent <= 0.0 {
+        return None;
+    }
+
+    let bucket_of = |primitive: &BuildPrimitive| -> usize {
+        let relative = (primitive.centroid.component(axis) - min) / extent;
+        usize::min((relative * (NUM_SAH_BUCKETS as Float)) as usize, NUM_SAH_BUCKETS - 1)
+    };
+
+    let mut counts = [0usize; NUM_SAH_BUCKETS];
+    let mut bucket_bounds: Vec<Aabb> = vec![Aabb::empty(); NUM_SAH_BUCKETS];
+    for primitive in primitives.iter() {
+        let bucket = bucket_of(primitive);
+        counts[bucket] += 1;
+        bucket_bounds[bucket] = bucket_bounds[bucket].union(&primitive.bounds);
+    }
+
+    // Splitting after bucket `i` puts buckets `0..=i` in the first child. We sweep from
+    // either end to get the cost of each side of every split.
+    let mut costs = [0.0 as Float; NUM_SAH_BUCKETS - 1];
+
+    let mut running_bounds = Aabb::empty();
+    let mut running_count = 0;
+    for i in 0..(NUM_SAH_BUCKETS - 1) {
+        running_bounds = running_bounds.union(&bucket_bounds[i]);
+        running_count += counts[i];
+        costs[i] += (running_count as Float) * running_bounds.surface_area();
+    }
+
+    running_bounds = Aabb::empty();
+    running_count = 0;
+    for i in (1..NUM_SAH_BUCKETS).rev() {
+        running_bounds = running_bounds.union(&bucket_bounds[i]);
+        running_count += counts[i];
+        costs[i - 1] += (running_count as Float) * running_bounds.surface_area();
+    }
+
+    let mut best_bucket = 0;
+    for i in 1..(NUM_SAH_BUCKETS - 1) {
+        if costs[i] < costs[best_bucket] {
+            best_bucket = i;
+        }
+    }
+
+    let mut mid = 0;
+    for i in 0..primitives.len() {
+        if bucket_of(&primitives[i]) <= best_bucket {
+            primitives.swap(mid, i);
+            mid += 1;
+        }
+    }
+
+    if mid == 0 || mid == primitives.len() {
+        return None;
+    }
+    Some(mid)
+}
+
+// E==== CONSTRUCTION }}}1
+
+// S==== TRAVERSAL {{{1
+
+impl Bvh {
+    /// Calls `intersect_primitive` with the index of every primitive in a leaf that the
+    /// ray passes through. It should return the $t$ at which the (provided) ray hits that
+    /// primitive, if it does. We use this to shrink the range of the ray, so that nodes
+    /// entirely behind the closest hit so far are skipped. To make that happen as early as
+    /// possible, the child nearer to the ray's origin (going by the split axis) is visited
+    /// first.
+    pub fn traverse<F>(&self, ray: &Ray3, mut intersect_primitive: F)
+    where
+        F: FnMut(usize, &Ray3) -> Option<Float>
+    {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut working_ray = ray.clone();
+        let inverse_direction = inverse_direction_of(ray);
+        let mut stack: Vec<usize> = vec![0];
+
+        while let Some(node_index) = stack.pop() {
+            match &self.nodes[node_index] {
+                BvhNode::Interior { bounds, children, axis } => {
+                    if bounds.hit_interval(&working_ray, &inverse_direction).is_none() { 
+                        continue; 
+                    }
+
+                    // The node popped first is visited first.
+                    if inverse_direction.component(*axis) < 0.0 {
+                        stack.push(children[0]);
+                        stack.push(children[1]);
+                    } else {
+                        stack.push(children[1]);
+                        stack.push(children[0]);
+                    }
+                },
+                BvhNode::Leaf { bounds, first, count } => {
+                    if bounds.hit_interval(&working_ray, &inverse_direction).is_none() { 
+                        continue; 
+                    }
+
+                    for &primitive_index in &self.primitive_indices[*first..(*first + *count)] {
+                        if let Some(t) = intersect_primitive(primitive_index, &working_ray) {
+                            working_ray.max_t = Float::min(working_ray.max_t, t);
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+// E==== TRAVERSAL }}}1
+
+// S==== TESTS {{{1
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::math::vector::{Vec3, dot};
+    use super::*;
+
+    /// Nearest hit of `ray` with the sphere, if it is in range.
+    fn intersect_sphere(ray: &Ray3, center: &Point3, radius: Float) -> Option<Float> {
+        let oc = &ray.origin - center;
+        let a = dot(&ray.direction, &ray.direction);
+        let b = 2.0 * dot(&ray.direction, &oc);
+        let c = dot(&oc, &oc) - radius * radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let t = (-b - Float::sqrt(discriminant)) / (2.0 * a);
+        if ray.is_in_range(t) { Some(t) } else { None }
+    }
+
+    #[test]
+    fn matches_linear_scan() {
+        let mut rng = RandomNumberGenerator::from_seed(3);
+        let mut random_point = |scale: Float| Point3::new(
+            scale * (rng.next_float() - 0.5),
+            scale * (rng.next_float() - 0.5),
+            scale * (rng.next_float() - 0.5)
+        );
+
+        let spheres: Vec<(Point3, Float)> = (0..200).map(|i| (random_point(20.0), 0.1 + 0.01 * (i % 50) as Float)).collect();
+        let bounds: Vec<Aabb> = spheres.iter()
+            .map(|(center, radius)| Aabb::new(
+                center - Vec3::new(*radius, *radius, *radius),
+                center + Vec3::new(*radius, *radius, *radius)
+            ))
+            .collect();
+        let rays: Vec<Ray3> = (0..200).map(|_| Ray3::new(random_point(30.0), random_point(1.0))).collect();
+
+        let methods = [
+            AccStructureAxisSelectionMethod::Random,
+            AccStructureAxisSelectionMethod::Alternating,
+            AccStructureAxisSelectionMethod::LargestExtent,
+            AccStructureAxisSelectionMethod::SurfaceAreaHeuristic,
+        ];
+        for method in methods.iter() {
+            let bvh = Bvh::new(&bounds, method);
+
+            for ray in rays.iter() {
+                let expected = spheres.iter()
+                    .filter_map(|(center, radius)| intersect_sphere(ray, center, *radius))
+                    .fold(Float::INFINITY, Float::min);
+
+                let mut closest = Float::INFINITY;
+                bvh.traverse(ray, |index, working_ray| {
+                    let t = intersect_sphere(working_ray, &spheres[index].0, spheres[index].1)?;
+                    closest = Float::min(closest, t);
+                    Some(t)
+                });
+
+                assert_eq!(closest, expected);
+            }
+        }
+    }
+}
+
+// E==== TESTS }}}1
@@ -0,0 +1,190 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::traits::ShapeIntersectionInfo,
+    utility::{
+        math::{
+            ray::Ray3,
+            float::Float,
+            vector::{Vec3, dot}
+        },
+        rng::RandomNumberGenerator
+    },
+    light::{Spectrum, ColorConstantsQueryable}
+};
+use super::{
+    traits::{MaterialLike, MaterialScatterResult},
+    microfacet::{TrowbridgeReitz, LocalFrame}
+};
+
+// E==== IMPORTS }}}1
+
+/// A rough metal, made up of mirror-like facets distributed by GGX. The Fresnel
+/// reflectance of the facets follows Schlick's approximation, starting from `reflectance`
+/// when seen head on and going to white at grazing angles.
+pub struct Conductor {
+    /// The fraction of light reflected at normal incidence, per channel.
+    reflectance: Spectrum,
+    distribution: TrowbridgeReitz,
+}
+
+impl Conductor {
+    pub fn new(reflectance: Spectrum, roughness: Float) -> Self {
+        Self {
+            reflectance,
+            distribution: TrowbridgeReitz::new_from_roughness(roughness),
+        }
+    }
+
+    fn fresnel(&self, cos_theta: Float) -> Spectrum {
+        let weight = Float::powi(1.0 - Float::clamp(cos_theta, 0.0, 1.0), 5);
+        (1.0 - weight) * &self.reflectance + weight * Spectrum::white()
+    }
+
+    /// $f \cos\theta_i$, for local directions.
+    fn eval_local(&self, wo: &Vec3, wi: &Vec3) -> Spectrum {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Spectrum::black();
+        }
+
+        let wm = (wo + wi).normalize();
+        let d = self.distribution.d(&wm);
+        let g = self.distribution.g(wo, wi);
+
+        (d * g / (4.0 * wo.z())) * self.fresnel(dot(wo, &wm))
+    }
+
+    /// Facet normals are sampled as seen from `wo`, and reflecting about the facet
+    /// normal halves the angle, so densities shrink by the Jacobian $4 |\omega_o \cdot
+    /// \omega_m|$.
+    fn pdf_local(&self, wo: &Vec3, wi: &Vec3) -> Float {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+
+        let wm = (wo + wi).normalize();
+        self.distribution.visible_normal_pdf(wo, &wm) / (4.0 * Float::abs(dot(wo, &wm)))
+    }
+}
+
+impl MaterialLike for Conductor {
+    fn sample(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        rng: &mut RandomNumberGenerator
+    ) -> MaterialScatterResult {
+        let frame = LocalFrame::new(incoming_ray, shape_intersection_info);
+        let wo = &frame.wo;
+        let absorbed = MaterialScatterResult {
+            did_scatter: false,
+            scattered_ray: Ray3::default(),
+            pdf: 0.0,
+            is_specular: false,
+            weight: Spectrum::black(),
+        };
+        if wo.z() <= 0.0 {
+            return absorbed;
+        }
+
+        let (wi, pdf, is_specular, weight) = if self.distribution.is_effectively_smooth() {
+            let wi = Vec3::new(-wo.x(), -wo.y(), wo.z());
+            (wi, 1.0, true, self.fresnel(wo.z()))
+        } else {
+            let wm = self.distribution.sample_visible_normal(wo, rng);
+            let wi = (2.0 * dot(wo, &wm)) * &wm - wo;
+            let pdf = self.pdf_local(wo, &wi);
+            if pdf <= 0.0 {
+                return absorbed;
+            }
+
+            let weight = (1.0 / pdf) * self.eval_local(wo, &wi);
+            (wi, pdf, false, weight)
+        };
+
+        let scattered_direction = frame.basis.vector_from_local(wi);
+        // The shading normal may send the reflection into the surface.
+        if dot(&scattered_direction, frame.geometric_normal.as_vec3()) <= 0.0 {
+            return absorbed;
+        }
+
+        MaterialScatterResult {
+            did_scatter: true,
+            scattered_ray: Ray3::new_from_surface(
+                &shape_intersection_info.point,
+                &frame.geometric_normal,
+                scattered_direction
+            ),
+            pdf,
+            is_specular,
+            weight,
+        }
+    }
+
+    fn eval(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Spectrum {
+        if self.distribution.is_effectively_smooth() {
+            return Spectrum::black();
+        }
+
+        let frame = LocalFrame::new(incoming_ray, shape_intersection_info);
+        let wi = frame.basis.vector_to_local(&scattered_direction.clone().normalize());
+        self.eval_local(&frame.wo, &wi)
+    }
+
+    fn pdf(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Float {
+        if self.distribution.is_effectively_smooth() {
+            return 0.0;
+        }
+
+        let frame = LocalFrame::new(incoming_ray, shape_intersection_info);
+        let wi = frame.basis.vector_to_local(&scattered_direction.clone().normalize());
+        self.pdf_local(&frame.wo, &wi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::math::vector::{Point3, Normal3};
+    use super::*;
+
+    #[test]
+    fn sampling_agrees_with_eval_and_pdf() {
+        let conductor = Conductor::new(Spectrum::new(0.9, 0.6, 0.2), 0.5);
+        let normal = Normal3::new(0.0, 1.0, 0.0);
+        let shape_intersection_info = ShapeIntersectionInfo {
+            did_hit: true,
+            point: Point3::origin(),
+            t: 1.0,
+            geometric_normal: normal.clone(),
+            shading_normal: normal,
+            ..Default::default()
+        };
+        let incoming_ray = Ray3::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let mut rng = RandomNumberGenerator::from_seed(5);
+
+        for _ in 0..1000 {
+            let result = conductor.sample(&incoming_ray, &shape_intersection_info, &mut rng);
+            if !result.did_scatter {
+                continue;
+            }
+
+            let direction = &result.scattered_ray.direction;
+            let pdf = conductor.pdf(&incoming_ray, &shape_intersection_info, direction);
+            let eval = conductor.eval(&incoming_ray, &shape_intersection_info, direction);
+            assert!(!result.is_specular && direction.y() > 0.0);
+            assert!((pdf - result.pdf).abs() <= 1e-3 * pdf.max(1.0));
+            assert!(Vec3::are_equal(&((1.0 / pdf) * eval), &result.weight));
+        }
+    }
+}
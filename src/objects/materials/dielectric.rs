@@ -0,0 +1,321 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::traits::ShapeIntersectionInfo,
+    utility::{
+        math::{
+            ray::Ray3,
+            float::Float,
+            vector::{Vec3, dot, reflect},
+            orthonormal_basis::OrthonormalBasis
+        },
+        rng::RandomNumberGenerator
+    },
+    light::{Spectrum, ColorConstantsQueryable}
+};
+use super::{
+    traits::{MaterialLike, MaterialScatterResult},
+    microfacet::TrowbridgeReitz
+};
+
+// E==== IMPORTS }}}1
+
+/// A transparent material like glass or water. Part of the light is reflected off the
+/// surface and the rest refracts through it, in proportions given by the Fresnel
+/// equations. We pick one of the two at random, with those proportions as probabilities.
+///
+/// Smooth surfaces do so specularly. Rough ones are made up of smooth facets distributed
+/// by GGX, each reflecting and refracting like a smooth surface (Walter et al., 
+/// "Microfacet models for refraction through rough surfaces").
+///
+/// The index of refraction is that of the inside of the shape (the side its normals
+/// point away from), with the outside taken to be vacuum. Radiance is squeezed into a
+/// smaller solid angle when it enters a denser medium, so refracted light is scaled by
+/// the square of the ratio of the indices.
+pub struct Dielectric {
+    index_of_refraction: Float,
+    distribution: TrowbridgeReitz,
+    /// Multiplies the light passing through, or reflected off, the surface.
+    tint: Spectrum,
+}
+
+/// The directions of a scattering event, in a frame where the shading normal (on the
+/// outside of the surface) is the $z$-axis.
+struct RefractionFrame {
+    basis: OrthonormalBasis,
+    /// Towards the origin of the incoming ray.
+    wo: Vec3,
+}
+
+impl Dielectric {
+    pub fn new(index_of_refraction: Float, roughness: Float, tint: Spectrum) -> Self {
+        Self {
+            index_of_refraction,
+            distribution: TrowbridgeReitz::new_from_roughness(roughness),
+            tint,
+        }
+    }
+
+    fn refraction_frame(incoming_ray: &Ray3, shape_intersection_info: &ShapeIntersectionInfo) -> RefractionFrame {
+        let wo = -1.0 * incoming_ray.direction.clone().normalize();
+        let shading_normal = shape_intersection_info.shading_normal
+            .facing(shape_intersection_info.geometric_normal.as_vec3());
+        let basis = OrthonormalBasis::new_from_vector(shading_normal.as_vec3());
+
+        RefractionFrame {
+            wo: basis.vector_to_local(&wo),
+            basis,
+        }
+    }
+
+    /// The fraction of light arriving from `w` (on either side) that a facet with normal
+    /// `wm` reflects.
+    fn fresnel(&self, w: &Vec3, wm: &Vec3) -> Float {
+        let cos_theta = dot(w, wm);
+        if cos_theta >= 0.0 {
+            fresnel_dielectric(cos_theta, 1.0 / self.index_of_refraction)
+        } else {
+            fresnel_dielectric(-cos_theta, self.index_of_refraction)
+        }
+    }
+
+    /// The normal of the facet that scatters `wo` into `wi`, in the upper hemisphere,
+    /// along with the ratio of the index of refraction on the side of `wi` to that on the 
+    /// side of `wo` (1 for reflection). `None` for configurations no facet produces.
+    fn facet_normal(&self, wo: &Vec3, wi: &Vec3) -> Option<(Vec3, Float)> {
+        if wo.z() == 0.0 || wi.z() == 0.0 {
+            return None;
+        }
+
+        let is_reflection = wo.z() * wi.z() > 0.0;
+        let eta = match (is_reflection, wo.z() > 0.0) {
+            (true, _) => 1.0,
+            (false, true) => self.index_of_refraction,
+            (false, false) => 1.0 / self.index_of_refraction,
+        };
+
+        let wm = eta * wi + wo;
+        if wm.length() == 0.0 {
+            return None;
+        }
+        let wm = wm.normalize();
+        let wm = if wm.z() < 0.0 { -1.0 * wm } else { wm };
+
+        // Both directions have to be on the visible side of the facet.
+        if dot(&wm, wi) * wi.z() < 0.0 || dot(&wm, wo) * wo.z() < 0.0 {
+            return None;
+        }
+
+        Some((wm, eta))
+    }
+
+    /// $f \cos\theta_i$ and the pdf with which `sample_rough()` picks `wi`.
+    fn eval_and_pdf_local(&self, wo: &Vec3, wi: &Vec3) -> (Spectrum, Float) {
+        let (wm, eta) = match self.facet_normal(wo, wi) {
+            Some(found) => found,
+            None => { return (Spectrum::black(), 0.0); }
+        };
+
+        let reflectance = self.fresnel(wo, &wm);
+        let d = self.distribution.d(&wm);
+        let g = self.distribution.g(wo, wi);
+        let visible_normal_pdf = self.distribution.visible_normal_pdf(wo, &wm);
+
+        let (f_cos, pdf) = if wo.z() * wi.z() > 0.0 {
+            let f_cos = reflectance * d * g / (4.0 * Float::abs(wo.z()));
+            let pdf = visible_normal_pdf / (4.0 * Float::abs(dot(wo, &wm))) * reflectance;
+            (f_cos, pdf)
+        } else {
+            // How the facet normal changes with the refracted direction.
+            let denominator = {
+                let sum = dot(wi, &wm) + dot(wo, &wm) / eta;
+                sum * sum
+            };
+            let f_cos = (1.0 - reflectance) * d * g
+                * Float::abs(dot(wi, &wm) * dot(wo, &wm) / (wo.z() * denominator))
+                / (eta * eta);
+            let pdf = visible_normal_pdf * Float::abs(dot(wi, &wm)) / denominator * (1.0 - reflectance);
+            (f_cos, pdf)
+        };
+
+        (f_cos * &self.tint, pdf)
+    }
+
+    fn sample_smooth(&self, frame: &RefractionFrame, rng: &mut RandomNumberGenerator) -> (Vec3, Spectrum) {
+        let wo = &frame.wo;
+        let normal = Vec3::new(0.0, 0.0, if wo.z() >= 0.0 { 1.0 } else { -1.0 });
+        // The ratio of the index of refraction we are leaving to the one we are entering.
+        let eta = if wo.z() >= 0.0 {
+            1.0 / self.index_of_refraction
+        } else {
+            self.index_of_refraction
+        };
+        let incoming_direction = -1.0 * wo;
+
+        match refract(&incoming_direction, &normal, eta) {
+            Some(refracted) if rng.next_float() >= fresnel_dielectric(Float::abs(wo.z()), eta) => {
+                (refracted, (eta * eta) * &self.tint)
+            },
+            // Either total internal reflection, or we chose to reflect.
+            _ => (reflect(&incoming_direction, &normal), self.tint.clone()),
+        }
+    }
+
+    /// Returns the scattered direction and its pdf, if any.
+    fn sample_rough(&self, frame: &RefractionFrame, rng: &mut RandomNumberGenerator) -> Option<(Vec3, Float)> {
+        let wo = &frame.wo;
+        let wm = self.distribution.sample_visible_normal(wo, rng);
+        let cos_theta_o = dot(wo, &wm);
+
+        let wi = if rng.next_float() < self.fresnel(wo, &wm) {
+            (2.0 * cos_theta_o) * &wm - wo
+        } else {
+            let (normal, eta) = if cos_theta_o >= 0.0 {
+                (wm.clone(), 1.0 / self.index_of_refraction)
+            } else {
+                (-1.0 * &wm, self.index_of_refraction)
+            };
+            refract(&(-1.0 * wo), &normal, eta)?
+        };
+
+        let (_, pdf) = self.eval_and_pdf_local(wo, &wi);
+        (pdf > 0.0).then_some((wi, pdf))
+    }
+}
+
+impl MaterialLike for Dielectric {
+    fn sample(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        rng: &mut RandomNumberGenerator
+    ) -> MaterialScatterResult {
+        let frame = Self::refraction_frame(incoming_ray, shape_intersection_info);
+
+        let (wi, pdf, is_specular, weight) = if self.distribution.is_effectively_smooth() {
+            let (wi, weight) = self.sample_smooth(&frame, rng);
+            (wi, 1.0, true, weight)
+        } else {
+            match self.sample_rough(&frame, rng) {
+                Some((wi, pdf)) => {
+                    let (f_cos, _) = self.eval_and_pdf_local(&frame.wo, &wi);
+                    (wi, pdf, false, (1.0 / pdf) * f_cos)
+                },
+                None => {
+                    return MaterialScatterResult {
+                        did_scatter: false,
+                        scattered_ray: Ray3::default(),
+                        pdf: 0.0,
+                        is_specular: false,
+                        weight: Spectrum::black(),
+                    };
+                }
+            }
+        };
+
+        // `new_from_surface` offsets the origin to whichever side the direction is on.
+        let scattered_ray = Ray3::new_from_surface(
+            &shape_intersection_info.point,
+            &shape_intersection_info.geometric_normal,
+            frame.basis.vector_from_local(wi)
+        );
+
+        MaterialScatterResult {
+            did_scatter: true,
+            scattered_ray,
+            pdf,
+            is_specular,
+            weight,
+        }
+    }
+
+    fn eval(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Spectrum {
+        if self.distribution.is_effectively_smooth() {
+            return Spectrum::black();
+        }
+
+        let frame = Self::refraction_frame(incoming_ray, shape_intersection_info);
+        let wi = frame.basis.vector_to_local(&scattered_direction.clone().normalize());
+        self.eval_and_pdf_local(&frame.wo, &wi).0
+    }
+
+    fn pdf(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Float {
+        if self.distribution.is_effectively_smooth() {
+            return 0.0;
+        }
+
+        let frame = Self::refraction_frame(incoming_ray, shape_intersection_info);
+        let wi = frame.basis.vector_to_local(&scattered_direction.clone().normalize());
+        self.eval_and_pdf_local(&frame.wo, &wi).1
+    }
+}
+
+/// The direction of a unit vector after passing into a medium through a surface with
+/// unit normal `normal` (on the side `direction` comes from), by Snell's law. `eta` is
+/// the ratio of the index of refraction of the medium being left to that of the medium
+/// being entered. `None` if there is total internal reflection.
+pub fn refract(direction: &Vec3, normal: &Vec3, eta: Float) -> Option<Vec3> {
+    let cos_theta_i = Float::min(1.0, -dot(direction, normal));
+    let sin_theta_t_squared = eta * eta * Float::max(0.0, 1.0 - cos_theta_i * cos_theta_i);
+    if sin_theta_t_squared >= 1.0 {
+        return None;
+    }
+
+    let cos_theta_t = Float::sqrt(1.0 - sin_theta_t_squared);
+    Some(eta * direction + (eta * cos_theta_i - cos_theta_t) * normal)
+}
+
+/// The fraction of unpolarized light that a dielectric interface reflects, for light
+/// arriving at an angle with cosine `cos_theta_i` to the normal. `eta` is as in
+/// `refract()`. This averages the reflectances of the two polarizations given by the
+/// Fresnel equations, and is 1 under total internal reflection.
+pub fn fresnel_dielectric(cos_theta_i: Float, eta: Float) -> Float {
+    let cos_theta_i = Float::clamp(cos_theta_i, 0.0, 1.0);
+    let sin_theta_t_squared = eta * eta * (1.0 - cos_theta_i * cos_theta_i);
+    if sin_theta_t_squared >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = Float::sqrt(1.0 - sin_theta_t_squared);
+
+    let r_perpendicular = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_parallel = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+
+    0.5 * (r_perpendicular * r_perpendicular + r_parallel * r_parallel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fresnel_and_snell() {
+        // Head on, glass reflects ((1 - 1.5) / (1 + 1.5))^2 = 4% either way.
+        assert!((fresnel_dielectric(1.0, 1.0 / 1.5) - 0.04).abs() < 1e-5);
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-5);
+        // Grazing light is all reflected.
+        assert!((fresnel_dielectric(0.0, 1.0 / 1.5) - 1.0).abs() < 1e-5);
+
+        // Leaving glass at 45 degrees is past the critical angle (about 41.8 degrees).
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let at_45_degrees = Vec3::new(1.0, 0.0, -1.0).normalize();
+        assert!(refract(&at_45_degrees, &normal, 1.5).is_none());
+        assert_eq!(fresnel_dielectric(Float::sqrt(0.5), 1.5), 1.0);
+
+        // Entering glass, sin(theta_t) = sin(theta_i) / 1.5.
+        let refracted = refract(&at_45_degrees, &normal, 1.0 / 1.5).unwrap();
+        assert!((refracted.length() - 1.0).abs() < 1e-5);
+        assert!((refracted.x() - Float::sqrt(0.5) / 1.5).abs() < 1e-5);
+        assert!(refracted.z() < 0.0);
+    }
+}
@@ -0,0 +1,82 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    objects::{shapes::traits::ShapeIntersectionInfo, textures::traits::TextureLike},
+    utility::{
+        math::{ray::Ray3, float::Float, vector::{Vec3, dot}}, 
+        rng::RandomNumberGenerator
+    }, 
+    light::{Spectrum, ColorConstantsQueryable}
+};
+use super::traits::{MaterialLike, MaterialScatterResult};
+
+// E==== IMPORTS }}}1
+
+/// An emitter that gives off light equally in all directions on the side of the surface 
+/// its normal points towards. It does not scatter any light.
+pub struct DiffuseLight {
+    radiance: Arc<dyn TextureLike>,
+}
+
+impl DiffuseLight {
+    pub fn new(radiance: Arc<dyn TextureLike>) -> Self {
+        Self {
+            radiance,
+        }
+    }
+}
+
+impl MaterialLike for DiffuseLight {
+    fn sample(
+        &self,
+        _incoming_ray: &Ray3, 
+        _shape_intersection_info: &ShapeIntersectionInfo, 
+        _rng: &mut RandomNumberGenerator
+    ) -> MaterialScatterResult {
+        MaterialScatterResult {
+            did_scatter: false,
+            scattered_ray: Ray3::default(),
+            pdf: 0.0,
+            is_specular: false,
+            weight: Spectrum::black(),
+        }
+    }
+
+    fn eval(
+        &self,
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo,
+        _scattered_direction: &Vec3
+    ) -> Spectrum {
+        Spectrum::black()
+    }
+
+    fn pdf(
+        &self,
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo,
+        _scattered_direction: &Vec3
+    ) -> Float {
+        0.0
+    }
+
+    fn emitted(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo
+    ) -> Spectrum {
+        // The back side of the emitter is dark.
+        if dot(&incoming_ray.direction, shape_intersection_info.geometric_normal.as_vec3()) >= 0.0 {
+            return Spectrum::black();
+        }
+
+        let radiance = self.radiance.value_at(incoming_ray, &shape_intersection_info.texture_coordinates);
+        radiance.as_ref().clone()
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+}
@@ -5,9 +5,12 @@ use crate::{
     objects::shapes::traits::ShapeIntersectionInfo,
     utility::math::{
         ray::Ray3, 
-        orthonormal_basis::OrthonormalBasis
+        orthonormal_basis::OrthonormalBasis,
+        float::{Float, FloatConstants},
+        vector::{Vec3, dot}
     }, 
     sampler, 
+    light::{Spectrum, ColorConstantsQueryable}
 };
 use super::traits::{MaterialLike, MaterialScatterResult};
 
@@ -16,8 +19,37 @@ use super::traits::{MaterialLike, MaterialScatterResult};
 pub struct Lambertian {
 }
 
+impl Lambertian {
+    /// Lambertian surfaces are two-sided, so we scatter about whichever side of the 
+    /// surface the incoming ray arrived from.
+    fn normal_facing_ray(incoming_ray: &Ray3, shape_intersection_info: &ShapeIntersectionInfo) -> Vec3 {
+        let normal = shape_intersection_info.shading_normal.as_vec3().clone();
+        if dot(&normal, &incoming_ray.direction) > 0.0 {
+            -1.0 * normal
+        } else {
+            normal
+        }
+    }
+
+    /// The cosine of the angle between the scattered direction and the normal, or 0 if 
+    /// the direction goes into the surface.
+    fn cos_theta(
+        incoming_ray: &Ray3, 
+        shape_intersection_info: &ShapeIntersectionInfo, 
+        scattered_direction: &Vec3
+    ) -> Float {
+        let cos_theta = dot(
+            &Self::normal_facing_ray(incoming_ray, shape_intersection_info), 
+            &scattered_direction.clone().normalize()
+        );
+
+        Float::max(0.0, cos_theta)
+    }
+}
+
 impl MaterialLike for Lambertian {
-    fn scatter(
+    /// We sample proportionally to the cosine, so that the weight is always 1.
+    fn sample(
         &self,
         incoming_ray: &Ray3, 
         shape_intersection_info: &ShapeIntersectionInfo, 
@@ -25,18 +57,44 @@ impl MaterialLike for Lambertian {
     ) -> MaterialScatterResult {
         let sample_result = sampler::cosine_on_2sphere_hemisphere(rng);
 
+        let normal = Self::normal_facing_ray(incoming_ray, shape_intersection_info);
         let scattered_direction = {
-            let onb = OrthonormalBasis::new_from_vector(&shape_intersection_info.surface_normal);
+            let onb = OrthonormalBasis::new_from_vector(&normal);
             onb.vector_from_local(sample_result.point.clone())
         };
-        let scattered_ray = Ray3::new(shape_intersection_info.point.clone(), scattered_direction);
+        let scattered_ray = Ray3::new_from_surface(
+            &shape_intersection_info.point, 
+            &shape_intersection_info.geometric_normal, 
+            scattered_direction
+        );
 
 
         MaterialScatterResult {
             did_scatter: true,
             scattered_ray,
             pdf: sample_result.pdf,
+            is_specular: false,
+            weight: Spectrum::white(),
         }
     }
-}
 
+    /// Lambertian surfaces scatter equally in all directions, so $f = 1 / \pi$.
+    fn eval(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Spectrum {
+        let cos_theta = Self::cos_theta(incoming_ray, shape_intersection_info, scattered_direction);
+        (cos_theta * Float::get_1_pi()) * Spectrum::white()
+    }
+
+    fn pdf(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Float {
+        Self::cos_theta(incoming_ray, shape_intersection_info, scattered_direction) * Float::get_1_pi()
+    }
+}
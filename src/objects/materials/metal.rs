@@ -0,0 +1,126 @@
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::traits::ShapeIntersectionInfo,
+    utility::{
+        math::{ray::Ray3, float::Float, vector::{Vec3, dot, reflect}},
+        rng::RandomNumberGenerator
+    },
+    light::{Spectrum, ColorConstantsQueryable},
+    sampler
+};
+use super::traits::{MaterialLike, MaterialScatterResult};
+
+// E==== IMPORTS }}}1
+
+/// A conductor, which reflects light about the surface normal. A perfect mirror reflects
+/// every ray in exactly one direction. Rougher (e.g. brushed) metals are imitated by
+/// pushing the mirrored direction to a random point on a sphere of radius `fuzz` around
+/// its tip, so that the reflections blur.
+pub struct Metal {
+    /// The color of the reflections, as a fraction of the light reflected per channel.
+    tint: Spectrum,
+    /// Between 0 (a perfect mirror) and 1.
+    fuzz: Float,
+}
+
+impl Metal {
+    pub fn new(tint: Spectrum, fuzz: Float) -> Self {
+        Self {
+            tint,
+            fuzz: Float::clamp(fuzz, 0.0, 1.0),
+        }
+    }
+}
+
+impl MaterialLike for Metal {
+    fn sample(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        rng: &mut RandomNumberGenerator
+    ) -> MaterialScatterResult {
+        let incoming_direction = incoming_ray.direction.clone().normalize();
+        // Metals are two-sided, like Lambertian surfaces.
+        let geometric_normal = shape_intersection_info.geometric_normal
+            .facing(&(-1.0 * &incoming_direction));
+        let shading_normal = shape_intersection_info.shading_normal
+            .facing(geometric_normal.as_vec3());
+
+        let mirrored = reflect(&incoming_direction, shading_normal.as_vec3());
+        let scattered_direction = if self.fuzz > 0.0 {
+            mirrored + self.fuzz * sampler::uniform_on_2sphere(rng).point
+        } else {
+            mirrored
+        };
+
+        // Fuzz (or a shading normal) may push the reflection into the surface, in which
+        // case we consider the light absorbed.
+        let did_scatter = dot(&scattered_direction, geometric_normal.as_vec3()) > 0.0;
+        let scattered_ray = Ray3::new_from_surface(
+            &shape_intersection_info.point,
+            &geometric_normal,
+            scattered_direction
+        );
+
+        MaterialScatterResult {
+            did_scatter,
+            scattered_ray,
+            pdf: 1.0,
+            is_specular: true,
+            weight: self.tint.clone(),
+        }
+    }
+
+    /// Reflections are always specular (see `sample()`).
+    fn eval(
+        &self,
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo,
+        _scattered_direction: &Vec3
+    ) -> Spectrum {
+        Spectrum::black()
+    }
+
+    fn pdf(
+        &self,
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo,
+        _scattered_direction: &Vec3
+    ) -> Float {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::math::vector::{Point3, Normal3};
+    use super::*;
+
+    #[test]
+    fn polished_metal_mirrors_about_the_normal() {
+        let metal = Metal::new(Spectrum::new(0.9, 0.6, 0.2), 0.0);
+        let normal = Normal3::new(0.0, 1.0, 0.0);
+        let shape_intersection_info = ShapeIntersectionInfo {
+            did_hit: true,
+            point: Point3::origin(),
+            t: 1.0,
+            geometric_normal: normal.clone(),
+            shading_normal: normal,
+            ..Default::default()
+        };
+        let mut rng = RandomNumberGenerator::from_seed(1);
+
+        // The same reflection from either side of the surface.
+        for direction in [Vec3::new(1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0)] {
+            let incoming_ray = Ray3::new(Point3::new(-1.0, 1.0, 0.0), direction.clone());
+            let result = metal.sample(&incoming_ray, &shape_intersection_info, &mut rng);
+
+            let expected = Vec3::new(1.0, -direction.y(), 0.0).normalize();
+            assert!(result.did_scatter && result.is_specular);
+            assert!(Vec3::are_equal(&result.scattered_ray.direction, &expected));
+            assert!(Vec3::are_equal(&result.weight, &Spectrum::new(0.9, 0.6, 0.2)));
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Rough surfaces modelled as a collection of tiny mirror-like facets, whose normals are
+//! distributed around the (shading) normal of the surface. All directions here are in a
+//! local frame where the surface normal is the $z$-axis, and point away from the surface.
+
+// S==== IMPORTS {{{1
+
+use crate::{
+    objects::shapes::traits::ShapeIntersectionInfo,
+    utility::{
+        math::{
+            ray::Ray3,
+            float::{Float, FloatConstants},
+            vector::{Vec3, Normal3, cross, dot},
+            orthonormal_basis::OrthonormalBasis
+        },
+        rng::RandomNumberGenerator
+    }
+};
+
+// E==== IMPORTS }}}1
+
+/// Below this $\alpha$, a surface is considered smooth and treated as specular, as the
+/// distribution gets too peaked to evaluate reliably.
+const SMOOTH_ALPHA: Float = 1.0e-3;
+
+/// The directions of a scattering event, in a frame where the shading normal (flipped
+/// to the side the incoming ray came from) is the $z$-axis.
+pub struct LocalFrame {
+    pub basis: OrthonormalBasis,
+    /// Towards the origin of the incoming ray.
+    pub wo: Vec3,
+    /// The geometric normal, on the same side as `wo`.
+    pub geometric_normal: Normal3,
+}
+
+impl LocalFrame {
+    /// Like Lambertian surfaces, materials that scatter in this frame are two-sided: 
+    /// whichever side the ray arrives from is treated as the outside.
+    pub fn new(incoming_ray: &Ray3, shape_intersection_info: &ShapeIntersectionInfo) -> Self {
+        let wo = -1.0 * incoming_ray.direction.clone().normalize();
+        let geometric_normal = shape_intersection_info.geometric_normal.facing(&wo);
+        let shading_normal = shape_intersection_info.shading_normal.facing(geometric_normal.as_vec3());
+        let basis = OrthonormalBasis::new_from_vector(shading_normal.as_vec3());
+
+        Self {
+            wo: basis.vector_to_local(&wo),
+            basis,
+            geometric_normal,
+        }
+    }
+}
+
+/// The (isotropic) Trowbridge-Reitz distribution, also known as GGX.
+#[derive(Clone, Debug)]
+pub struct TrowbridgeReitz {
+    /// The width of the distribution, roughly the slope of a typical facet.
+    alpha: Float,
+}
+
+impl TrowbridgeReitz {
+    /// Roughness in $[0, 1]$ is mapped to $\alpha = \text{roughness}^2$, which makes
+    /// evenly spaced values look about evenly spaced.
+    pub fn new_from_roughness(roughness: Float) -> Self {
+        let roughness = Float::clamp(roughness, 0.0, 1.0);
+        Self { alpha: roughness * roughness }
+    }
+
+    pub fn is_effectively_smooth(&self) -> bool {
+        self.alpha < SMOOTH_ALPHA
+    }
+
+    /// The density of facets with normal `wm`, per unit of (macro)surface area and
+    /// solid angle of normals, so that $\int D(\omega_m) \cos\theta_m \, d\omega_m = 1$.
+    pub fn d(&self, wm: &Vec3) -> Float {
+        let cos_squared = wm.z() * wm.z();
+        if cos_squared < 1.0e-16 {
+            return 0.0;
+        }
+
+        let tan_squared = (1.0 - cos_squared) / cos_squared;
+        let alpha_squared = self.alpha * self.alpha;
+        let e = 1.0 + tan_squared / alpha_squared;
+
+        1.0 / (Float::get_pi() * alpha_squared * cos_squared * cos_squared * e * e)
+    }
+
+    /// Smith's auxiliary function, measuring how much of the surface seen from `w` is
+    /// hidden behind other facets.
+    fn lambda(&self, w: &Vec3) -> Float {
+        let cos_squared = w.z() * w.z();
+        if cos_squared == 0.0 {
+            return 0.0;
+        }
+
+        let tan_squared = (1.0 - cos_squared) / cos_squared;
+        0.5 * (Float::sqrt(1.0 + self.alpha * self.alpha * tan_squared) - 1.0)
+    }
+
+    /// The fraction of facets visible from `w`.
+    pub fn g1(&self, w: &Vec3) -> Float {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// The fraction of facets visible from both `wo` and `wi`.
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> Float {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// The density of the normals of the facets seen from `w`, which is what
+    /// `sample_visible_normal()` samples.
+    pub fn visible_normal_pdf(&self, w: &Vec3, wm: &Vec3) -> Float {
+        let cos_theta = Float::abs(w.z());
+        if cos_theta == 0.0 {
+            return 0.0;
+        }
+
+        self.g1(w) / cos_theta * self.d(wm) * Float::abs(dot(w, wm))
+    }
+
+    /// Heitz's method: stretch the configuration so that the distribution becomes that of
+    /// a hemisphere, sample a point on the projection of the part of it seen from `w`,
+    /// and unstretch the normal there. The normal returned is in the upper hemisphere.
+    pub fn sample_visible_normal(&self, w: &Vec3, rng: &mut RandomNumberGenerator) -> Vec3 {
+        let wh = {
+            let wh = Vec3::new(self.alpha * w.x(), self.alpha * w.y(), w.z()).normalize();
+            if wh.z() < 0.0 { -1.0 * wh } else { wh }
+        };
+
+        let t1 = if wh.z() < 0.99999 {
+            Vec3::new(-wh.y(), wh.x(), 0.0).normalize()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = cross(&wh, &t1);
+
+        // A uniform point on the unit disk, with the half of it that is hidden when seen
+        // from `w` squashed into the visible half.
+        let r = Float::sqrt(rng.next_float());
+        let (sin_phi, cos_phi) = Float::sin_cos(2.0 * Float::get_pi() * rng.next_float());
+        let px = r * cos_phi;
+        let py = {
+            let h = Float::sqrt(1.0 - px * px);
+            let s = 0.5 * (1.0 + wh.z());
+            (1.0 - s) * h + s * r * sin_phi
+        };
+        let pz = Float::sqrt(Float::max(0.0, 1.0 - px * px - py * py));
+
+        let nh = px * &t1 + py * &t2 + pz * &wh;
+        Vec3::new(self.alpha * nh.x(), self.alpha * nh.y(), Float::max(1.0e-6, nh.z())).normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sampler;
+    use super::*;
+
+    #[test]
+    fn distributions_are_normalized() {
+        let distribution = TrowbridgeReitz::new_from_roughness(0.5);
+        let mut rng = RandomNumberGenerator::from_seed(3);
+        let w = Vec3::new(0.6, 0.0, 0.8);
+
+        // Estimate the integrals over the hemisphere of D cos and of the visible normal
+        // density, which should both be 1.
+        let num_samples = 200_000;
+        let (mut projected_area, mut visible) = (0.0, 0.0);
+        for _ in 0..num_samples {
+            let sample = sampler::uniform_on_2sphere_hemisphere(&mut rng);
+            let wm = sample.point;
+            projected_area += distribution.d(&wm) * wm.z() / sample.pdf;
+            visible += distribution.visible_normal_pdf(&w, &wm) / sample.pdf;
+        }
+        assert!((projected_area / num_samples as Float - 1.0).abs() < 0.02);
+        assert!((visible / num_samples as Float - 1.0).abs() < 0.02);
+
+        // Sampled normals are visible from `w`.
+        for _ in 0..1000 {
+            let wm = distribution.sample_visible_normal(&w, &mut rng);
+            assert!(wm.z() > 0.0 && dot(&w, &wm) > 0.0);
+            assert!((wm.length() - 1.0).abs() < 1e-4);
+        }
+    }
+}
@@ -1,7 +1,14 @@
 //! Each shape should have a material. When a ray intersects a surface, the 
 //! material determines how that ray scatters. That is precisely what a material 
-//! does in Mirth: determines the direction of the scattered ray. 
+//! does in Mirth: determines the direction of the scattered ray. A material may 
+//! also give off light of its own, which is how light sources enter the scene.
 
 pub mod traits;
 pub mod lambertian;
+pub mod diffuse_light;
+pub mod metal;
+pub mod dielectric;
+pub mod conductor;
+pub mod microfacet;
+pub mod principled;
 
@@ -0,0 +1,349 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    objects::{shapes::traits::ShapeIntersectionInfo, textures::traits::TextureLike},
+    utility::{
+        math::{
+            ray::Ray3,
+            float::{Float, FloatConstants},
+            vector::{Vec3, dot}
+        },
+        rng::RandomNumberGenerator,
+        post_process::luminance
+    },
+    sampler,
+    light::{Spectrum, ColorConstantsQueryable}
+};
+use super::{
+    traits::{MaterialLike, MaterialScatterResult},
+    microfacet::{TrowbridgeReitz, LocalFrame}
+};
+
+// E==== IMPORTS }}}1
+
+/// Roughnesses are clamped to at least this, so that the specular lobes can always be
+/// evaluated rather than becoming mirrors.
+const MIN_ROUGHNESS: Float = 0.05;
+/// The reflectance at normal incidence of the clearcoat, a layer of polyurethane with an
+/// index of refraction of 1.5.
+const CLEARCOAT_REFLECTANCE: Float = 0.04;
+/// The strength of the clearcoat when its parameter is 1, which keeps it a subtle
+/// highlight as in Disney's model.
+const CLEARCOAT_STRENGTH: Float = 0.25;
+
+/// The parameters of a `Principled` material. Colors are read from all three channels of
+/// their textures, and the other parameters, which are in $[0, 1]$, from the average of
+/// the channels. Constant parameters are given by `ConstantTexture`s.
+pub struct PrincipledParameters {
+    pub base_color: Arc<dyn TextureLike>,
+    /// 0 for dielectrics, 1 for metals.
+    pub metallic: Arc<dyn TextureLike>,
+    pub roughness: Arc<dyn TextureLike>,
+    /// The strength of the specular highlight of dielectrics. The default of 0.5 gives
+    /// the 4% reflectance of most common materials.
+    pub specular: Arc<dyn TextureLike>,
+    /// How much the specular highlight of dielectrics takes on the hue of the base color.
+    pub specular_tint: Arc<dyn TextureLike>,
+    /// The strength of a second, white specular layer on top of everything else.
+    pub clearcoat: Arc<dyn TextureLike>,
+    pub clearcoat_roughness: Arc<dyn TextureLike>,
+    /// The strength of the extra reflection at grazing angles that cloth shows.
+    pub sheen: Arc<dyn TextureLike>,
+    /// How much the sheen takes on the hue of the base color.
+    pub sheen_tint: Arc<dyn TextureLike>,
+}
+
+/// Disney's principled BRDF (Burley, "Physically-based shading at Disney"), which blends
+/// a diffuse base, a sheen for cloth, a GGX specular lobe and a clearcoat from a handful
+/// of intuitive parameters. These are what DCC tools export, so assets map onto it
+/// directly. Transmission is not supported; use `Dielectric` for glass.
+///
+/// The object's texture still multiplies the scattered light, so objects with this
+/// material would normally be given a white texture and have their color set through
+/// `base_color`.
+pub struct Principled {
+    parameters: PrincipledParameters,
+}
+
+/// The parameters of a `Principled` material at a particular point, turned into the
+/// quantities its lobes are evaluated with.
+struct Lobes {
+    base_color: Spectrum,
+    roughness: Float,
+    /// The weight of the diffuse and sheen lobes, which metals lack.
+    dielectric_weight: Float,
+    sheen_color: Spectrum,
+    /// The Fresnel reflectance of the specular lobe at normal incidence.
+    specular_reflectance: Spectrum,
+    specular_distribution: TrowbridgeReitz,
+    clearcoat_weight: Float,
+    clearcoat_distribution: TrowbridgeReitz,
+    /// The probabilities with which `sample()` picks the diffuse, specular and clearcoat
+    /// lobes, in that order.
+    selection_probabilities: [Float; 3],
+}
+
+impl Principled {
+    pub fn new(parameters: PrincipledParameters) -> Self {
+        Self {
+            parameters,
+        }
+    }
+
+    fn lobes_at(&self, incoming_ray: &Ray3, shape_intersection_info: &ShapeIntersectionInfo) -> Lobes {
+        let coordinates = &shape_intersection_info.texture_coordinates;
+        let color = |texture: &Arc<dyn TextureLike>| texture.value_at(incoming_ray, coordinates).as_ref().clone();
+        let scalar = |texture: &Arc<dyn TextureLike>| {
+            let value = texture.value_at(incoming_ray, coordinates);
+            Float::clamp((value.x() + value.y() + value.z()) / 3.0, 0.0, 1.0)
+        };
+
+        let base_color = color(&self.parameters.base_color);
+        let metallic = scalar(&self.parameters.metallic);
+        let roughness = Float::max(MIN_ROUGHNESS, scalar(&self.parameters.roughness));
+        let clearcoat_roughness = Float::max(MIN_ROUGHNESS, scalar(&self.parameters.clearcoat_roughness));
+        let clearcoat_weight = CLEARCOAT_STRENGTH * scalar(&self.parameters.clearcoat);
+
+        // The hue of the base color, with its brightness taken out.
+        let tint = {
+            let luminance = luminance(&base_color);
+            if luminance > 0.0 { (1.0 / luminance) * &base_color } else { Spectrum::white() }
+        };
+        let sheen_color = scalar(&self.parameters.sheen)
+            * lerp(scalar(&self.parameters.sheen_tint), &Spectrum::white(), &tint);
+        let specular_reflectance = lerp(
+            metallic,
+            &(0.08 * scalar(&self.parameters.specular)
+                * lerp(scalar(&self.parameters.specular_tint), &Spectrum::white(), &tint)),
+            &base_color
+        );
+
+        let dielectric_weight = 1.0 - metallic;
+        let selection_probabilities = {
+            let diffuse = dielectric_weight * luminance(&base_color);
+            let specular = luminance(&specular_reflectance).max(0.5 * (1.0 - diffuse));
+            let total = diffuse + specular + clearcoat_weight;
+            [diffuse / total, specular / total, clearcoat_weight / total]
+        };
+
+        Lobes {
+            base_color,
+            roughness,
+            dielectric_weight,
+            sheen_color,
+            specular_reflectance,
+            specular_distribution: TrowbridgeReitz::new_from_roughness(roughness),
+            clearcoat_weight,
+            clearcoat_distribution: TrowbridgeReitz::new_from_roughness(clearcoat_roughness),
+            selection_probabilities,
+        }
+    }
+}
+
+impl Lobes {
+    /// $f \cos\theta_i$, for local directions.
+    fn eval_local(&self, wo: &Vec3, wi: &Vec3) -> Spectrum {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Spectrum::black();
+        }
+
+        let wm = (wo + wi).normalize();
+        let cos_theta_d = dot(wi, &wm);
+
+        // The diffuse lobe is brightened at grazing angles for rough surfaces and darkened
+        // for smooth ones, to match measured materials.
+        let diffuse = {
+            let fd90 = 0.5 + 2.0 * self.roughness * cos_theta_d * cos_theta_d;
+            let retro_reflection = (1.0 + (fd90 - 1.0) * schlick_weight(wi.z()))
+                * (1.0 + (fd90 - 1.0) * schlick_weight(wo.z()));
+            (retro_reflection * Float::get_1_pi()) * &self.base_color
+        };
+        let sheen = schlick_weight(cos_theta_d) * &self.sheen_color;
+        let dielectric = (self.dielectric_weight * wi.z()) * (diffuse + sheen);
+
+        let specular = {
+            let d = self.specular_distribution.d(&wm);
+            let g = self.specular_distribution.g(wo, wi);
+            let fresnel = lerp(schlick_weight(cos_theta_d), &self.specular_reflectance, &Spectrum::white());
+            (d * g / (4.0 * wo.z())) * fresnel
+        };
+
+        let clearcoat = if self.clearcoat_weight > 0.0 {
+            let d = self.clearcoat_distribution.d(&wm);
+            let g = self.clearcoat_distribution.g(wo, wi);
+            let fresnel = CLEARCOAT_REFLECTANCE + (1.0 - CLEARCOAT_REFLECTANCE) * schlick_weight(cos_theta_d);
+            self.clearcoat_weight * d * g * fresnel / (4.0 * wo.z())
+        } else {
+            0.0
+        };
+
+        dielectric + specular + clearcoat * Spectrum::white()
+    }
+
+    /// The mixture of the densities of the lobes, the specular ones sampling reflections
+    /// of visible facet normals as `Conductor` does.
+    fn pdf_local(&self, wo: &Vec3, wi: &Vec3) -> Float {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+
+        let wm = (wo + wi).normalize();
+        let jacobian = 4.0 * Float::abs(dot(wo, &wm));
+        let [diffuse, specular, clearcoat] = self.selection_probabilities;
+
+        diffuse * wi.z() * Float::get_1_pi()
+            + specular * self.specular_distribution.visible_normal_pdf(wo, &wm) / jacobian
+            + clearcoat * self.clearcoat_distribution.visible_normal_pdf(wo, &wm) / jacobian
+    }
+
+    fn sample_local(&self, wo: &Vec3, rng: &mut RandomNumberGenerator) -> Vec3 {
+        let [diffuse, specular, _] = self.selection_probabilities;
+        let u = rng.next_float();
+
+        if u < diffuse {
+            return sampler::cosine_on_2sphere_hemisphere(rng).point;
+        }
+
+        let distribution = if u < diffuse + specular {
+            &self.specular_distribution
+        } else {
+            &self.clearcoat_distribution
+        };
+        let wm = distribution.sample_visible_normal(wo, rng);
+        (2.0 * dot(wo, &wm)) * &wm - wo
+    }
+}
+
+impl MaterialLike for Principled {
+    fn sample(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        rng: &mut RandomNumberGenerator
+    ) -> MaterialScatterResult {
+        let frame = LocalFrame::new(incoming_ray, shape_intersection_info);
+        let wo = &frame.wo;
+        let absorbed = MaterialScatterResult {
+            did_scatter: false,
+            scattered_ray: Ray3::default(),
+            pdf: 0.0,
+            is_specular: false,
+            weight: Spectrum::black(),
+        };
+        if wo.z() <= 0.0 {
+            return absorbed;
+        }
+
+        let lobes = self.lobes_at(incoming_ray, shape_intersection_info);
+        let wi = lobes.sample_local(wo, rng);
+        let pdf = lobes.pdf_local(wo, &wi);
+        if pdf <= 0.0 {
+            return absorbed;
+        }
+
+        let scattered_direction = frame.basis.vector_from_local(wi.clone());
+        // The shading normal may send the reflection into the surface.
+        if dot(&scattered_direction, frame.geometric_normal.as_vec3()) <= 0.0 {
+            return absorbed;
+        }
+
+        MaterialScatterResult {
+            did_scatter: true,
+            scattered_ray: Ray3::new_from_surface(
+                &shape_intersection_info.point,
+                &frame.geometric_normal,
+                scattered_direction
+            ),
+            pdf,
+            is_specular: false,
+            weight: (1.0 / pdf) * lobes.eval_local(wo, &wi),
+        }
+    }
+
+    fn eval(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Spectrum {
+        let frame = LocalFrame::new(incoming_ray, shape_intersection_info);
+        let wi = frame.basis.vector_to_local(&scattered_direction.clone().normalize());
+        self.lobes_at(incoming_ray, shape_intersection_info).eval_local(&frame.wo, &wi)
+    }
+
+    fn pdf(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Float {
+        let frame = LocalFrame::new(incoming_ray, shape_intersection_info);
+        let wi = frame.basis.vector_to_local(&scattered_direction.clone().normalize());
+        self.lobes_at(incoming_ray, shape_intersection_info).pdf_local(&frame.wo, &wi)
+    }
+}
+
+/// $(1 - \cos\theta)^5$, the weight of white in Schlick's approximation of Fresnel
+/// reflectance.
+fn schlick_weight(cos_theta: Float) -> Float {
+    Float::powi(1.0 - Float::clamp(cos_theta, 0.0, 1.0), 5)
+}
+
+fn lerp(t: Float, a: &Spectrum, b: &Spectrum) -> Spectrum {
+    (1.0 - t) * a + t * b
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        objects::textures::constant::ConstantTexture,
+        utility::math::vector::{Point3, Normal3}
+    };
+    use super::*;
+
+    fn constant(value: Float) -> Arc<dyn TextureLike> {
+        Arc::new(ConstantTexture::new_from_rgb(Spectrum::new(value, value, value)))
+    }
+
+    #[test]
+    fn sampling_agrees_with_eval_and_pdf() {
+        let principled = Principled::new(PrincipledParameters {
+            base_color: Arc::new(ConstantTexture::new_from_rgb(Spectrum::new(0.8, 0.3, 0.1))),
+            metallic: constant(0.3),
+            roughness: constant(0.4),
+            specular: constant(0.5),
+            specular_tint: constant(0.2),
+            clearcoat: constant(1.0),
+            clearcoat_roughness: constant(0.3),
+            sheen: constant(0.5),
+            sheen_tint: constant(0.5),
+        });
+        let normal = Normal3::new(0.0, 1.0, 0.0);
+        let shape_intersection_info = ShapeIntersectionInfo {
+            did_hit: true,
+            point: Point3::origin(),
+            t: 1.0,
+            geometric_normal: normal.clone(),
+            shading_normal: normal,
+            ..Default::default()
+        };
+        let incoming_ray = Ray3::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let mut rng = RandomNumberGenerator::from_seed(7);
+
+        for _ in 0..1000 {
+            let result = principled.sample(&incoming_ray, &shape_intersection_info, &mut rng);
+            if !result.did_scatter {
+                continue;
+            }
+
+            let direction = &result.scattered_ray.direction;
+            let pdf = principled.pdf(&incoming_ray, &shape_intersection_info, direction);
+            let eval = principled.eval(&incoming_ray, &shape_intersection_info, direction);
+            assert!(!result.is_specular && direction.y() > 0.0);
+            assert!((pdf - result.pdf).abs() <= 1e-3 * pdf.max(1.0));
+            assert!(Vec3::are_equal(&((1.0 / pdf) * eval), &result.weight));
+        }
+    }
+}
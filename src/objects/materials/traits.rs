@@ -1,22 +1,74 @@
 use crate::{
     utility::{
-        math::{float::Float, ray::Ray3}, 
+        math::{float::Float, ray::Ray3, vector::Vec3}, 
         rng::RandomNumberGenerator
     }, 
-    objects::shapes::traits::ShapeIntersectionInfo
+    objects::shapes::traits::ShapeIntersectionInfo,
+    light::{Spectrum, ColorConstantsQueryable}
 };
 
+/// A direction sampled by `MaterialLike::sample()`.
 pub struct MaterialScatterResult {
     pub did_scatter: bool,
     pub scattered_ray: Ray3,
+    /// The density, with respect to solid angle, with which the direction was sampled.
     pub pdf: Float,
+    /// Whether the direction was picked (nearly) deterministically, as by a mirror, 
+    /// rather than from a density that `pdf()` can evaluate. There is then no point in
+    /// sampling lights here, nor in weighing against light sampling.
+    pub is_specular: bool,
+    /// What the light arriving along the scattered ray is multiplied by on its way back
+    /// along the incoming ray (on top of the albedo given by the object's texture). This
+    /// is `eval()` divided by `pdf`, or its limit for specular directions.
+    pub weight: Spectrum,
 }
 
-pub trait MaterialLike {
-    fn scatter(
+/// Materials are described by their bidirectional scattering distribution function 
+/// (BSDF) $f(\omega_o, \omega_i)$: the fraction of the light arriving from direction 
+/// $\omega_i$ that leaves in direction $\omega_o$. Here $\omega_o$ points back along the 
+/// incoming ray, and $\omega_i$ is the direction we look for more light in, i.e. that of
+/// the scattered ray.
+pub trait MaterialLike: Send + Sync {
+    /// Samples a scattered direction, ideally with density proportional to $f$ times the
+    /// cosine of the angle it makes with the shading normal.
+    fn sample(
         &self,
         incoming_ray: &Ray3, 
         shape_intersection_info: &ShapeIntersectionInfo, 
         rng: &mut RandomNumberGenerator
     ) -> MaterialScatterResult;
+
+    /// $f(\omega_o, \omega_i) |\cos\theta_i|$ for $\omega_i$ = `scattered_direction`. This is
+    /// zero for materials that only scatter specularly.
+    fn eval(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Spectrum;
+
+    /// The density, with respect to solid angle, with which `sample()` would produce 
+    /// `scattered_direction`. This is zero for materials that only scatter specularly.
+    fn pdf(
+        &self,
+        incoming_ray: &Ray3,
+        shape_intersection_info: &ShapeIntersectionInfo,
+        scattered_direction: &Vec3
+    ) -> Float;
+
+    /// The light given off by the material at the intersection point, towards the origin 
+    /// of `incoming_ray`. Most materials don't emit anything.
+    fn emitted(
+        &self,
+        _incoming_ray: &Ray3,
+        _shape_intersection_info: &ShapeIntersectionInfo
+    ) -> Spectrum {
+        Spectrum::black()
+    }
+
+    /// Whether `emitted()` can ever be nonzero. Objects with emissive materials are 
+    /// treated as lights.
+    fn is_emissive(&self) -> bool {
+        false
+    }
 }
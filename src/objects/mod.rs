@@ -6,6 +6,7 @@
 pub mod traits;
 pub mod object;
 pub mod object_group;
+pub mod bvh;
 pub mod shapes;
 pub mod textures;
 pub mod materials;
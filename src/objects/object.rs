@@ -1,13 +1,16 @@
 
 // S==== IMPORTS {{{1
 
-use std::{rc::Rc, collections::HashMap};
-use crate::utility::{
-    math::ray::Ray3, 
-    rng::RandomNumberGenerator
+use std::sync::Arc;
+use crate::{
+    utility::{
+        math::{ray::Ray3, float::Float, vector::{Point3, Vec3}}, 
+        rng::RandomNumberGenerator
+    },
+    light::Spectrum
 };
 use super::{
-    shapes::{traits::{ShapeLike, ShapeIntersectionInfo}, quad::Quad, self}, 
+    shapes::traits::{ShapeLike, ShapeIntersectionInfo, ShapeSampleResult}, 
     textures::traits::TextureLike, 
     materials::traits::{MaterialLike, MaterialScatterResult}
 };
@@ -16,15 +19,15 @@ use tracing::error;
 // E==== IMPORTS }}}1
 
 pub struct Object {
-    pub(super) shape: Rc<dyn ShapeLike>,
-    texture: Rc<dyn TextureLike>,
-    material: Rc<dyn MaterialLike>,
+    pub(super) shape: Arc<dyn ShapeLike>,
+    texture: Arc<dyn TextureLike>,
+    material: Arc<dyn MaterialLike>,
 }
 
 pub struct ObjectInfo {
-    pub shape: Rc<dyn ShapeLike>,
-    pub texture: Rc<dyn TextureLike>,
-    pub material: Rc<dyn MaterialLike>,
+    pub shape: Arc<dyn ShapeLike>,
+    pub texture: Arc<dyn TextureLike>,
+    pub material: Arc<dyn MaterialLike>,
 }
 
 /// Parameter to `Object::sample_new_ray()`.
@@ -44,7 +47,53 @@ impl Object {
     }
 
     pub fn sample_new_ray(&self, info: SampleNewRayInfo) -> MaterialScatterResult {
-        self.material.scatter(info.incoming_ray, info.shape_intersection, info.rng)
+        self.material.sample(info.incoming_ray, info.shape_intersection, info.rng)
+    }
+
+    /// The color of the object at the intersection point, as determined by its texture.
+    pub fn albedo_at(&self, incoming_ray: &Ray3, shape_intersection: &ShapeIntersectionInfo) -> Spectrum {
+        let albedo = self.texture.value_at(incoming_ray, &shape_intersection.texture_coordinates);
+        albedo.as_ref().clone()
+    }
+
+    /// The light given off by the object at the intersection point, towards the origin
+    /// of `incoming_ray`. See `MaterialLike::emitted()`.
+    pub fn emitted(&self, incoming_ray: &Ray3, shape_intersection: &ShapeIntersectionInfo) -> Spectrum {
+        self.material.emitted(incoming_ray, shape_intersection)
+    }
+
+    /// Whether the object gives off light, in which case it should be treated as a light.
+    pub fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
     }
-}
 
+    /// Samples a point on the object's shape. See `SampleableShape::sample_from()`.
+    pub fn sample_shape_from(&self, reference: &Point3, rng: &mut RandomNumberGenerator) -> ShapeSampleResult {
+        self.shape.sample_from(reference, rng)
+    }
+
+    /// See `SampleableShape::pdf_from()`.
+    pub fn shape_pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        self.shape.pdf_from(reference, direction)
+    }
+
+    /// See `MaterialLike::eval()`.
+    pub fn eval_material(
+        &self, 
+        incoming_ray: &Ray3, 
+        shape_intersection: &ShapeIntersectionInfo, 
+        scattered_direction: &Vec3
+    ) -> Spectrum {
+        self.material.eval(incoming_ray, shape_intersection, scattered_direction)
+    }
+
+    /// See `MaterialLike::pdf()`.
+    pub fn material_pdf(
+        &self, 
+        incoming_ray: &Ray3, 
+        shape_intersection: &ShapeIntersectionInfo, 
+        scattered_direction: &Vec3
+    ) -> Float {
+        self.material.pdf(incoming_ray, shape_intersection, scattered_direction)
+    }
+}
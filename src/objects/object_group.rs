@@ -1,42 +1,105 @@
 
 // S==== IMPORTS {{{1
 
-use std::rc::Rc;
-use crate::utility::math::ray::Ray3;
+use std::sync::Arc;
+use crate::{
+    utility::math::{ray::Ray3, aabb::{Aabb, inverse_direction_of}},
+    config::{MIRTH_CONFIG, AccStructureKind}
+};
 use super::{
     object::Object,
-    shapes::traits::ShapeIntersectionInfo
+    shapes::traits::ShapeIntersectionInfo,
+    bvh::Bvh
 };
 
 // E==== IMPORTS }}}1
 
 pub struct ObjectGroup {
-    objects: Vec<Rc<Object>>,
+    objects: Vec<Arc<Object>>,
+    /// The bounding box of each object, in the same order as `objects`.
+    bounds: Vec<Aabb>,
+    /// Built over `objects`, unless `MIRTH_CONFIG` asks for no acceleration structure.
+    bvh: Option<Bvh>,
 }
 
 pub struct ObjectGroupIntersectionInfo {
-    pub intersected_object: Option<Rc<Object>>,
+    pub intersected_object: Option<Arc<Object>>,
     pub shape_intersection_info: ShapeIntersectionInfo,
 }
 
 impl ObjectGroup {
-    pub fn new_from_vector(objects: Vec<Rc<Object>>) -> Self {
-        Self { objects }
+    pub fn new_from_vector(objects: Vec<Arc<Object>>) -> Self {
+        let acceleration_structure = &MIRTH_CONFIG.acceleration_structure;
+        let bounds: Vec<Aabb> = objects.iter()
+            .map(|object| object.shape.bounding_box())
+            .collect();
+
+        let bvh = match acceleration_structure.kind {
+            AccStructureKind::Nothing => None,
+            AccStructureKind::BBH => {
+                Some(Bvh::new(&bounds, &acceleration_structure.axis_selection_method))
+            },
+        };
+
+        Self { objects, bounds, bvh }
+    }
+
+    pub fn objects(&self) -> &[Arc<Object>] {
+        &self.objects
     }
 
     pub fn intersect(&self, ray: &Ray3) -> ObjectGroupIntersectionInfo {
-        self.intersect_unoptimized(ray)
+        let mut to_return = match &self.bvh {
+            Some(bvh) => self.intersect_with_bvh(bvh, ray),
+            None => self.intersect_unoptimized(ray),
+        };
+
+        // Only the closest hit is worth this.
+        if to_return.intersected_object.is_some() {
+            to_return.shape_intersection_info.compute_texture_derivatives(ray);
+        }
+
+        to_return
     }
 
-    /// Go through each object in the scene and check for intersection.
+    /// Only check for intersection with the objects the hierarchy can't rule out.
+    fn intersect_with_bvh(&self, bvh: &Bvh, ray: &Ray3) -> ObjectGroupIntersectionInfo {
+        let mut to_return = ObjectGroupIntersectionInfo {
+            intersected_object: None,
+            shape_intersection_info: ShapeIntersectionInfo::default(),
+        };
+
+        bvh.traverse(ray, |index, working_ray| {
+            let object = &self.objects[index];
+            let shape_intersection_info = object.shape.intersect(working_ray);
+
+            if !shape_intersection_info.did_hit { return None; }
+            if shape_intersection_info.t > to_return.shape_intersection_info.t { return None; }
+
+            let t = shape_intersection_info.t;
+            to_return = ObjectGroupIntersectionInfo {
+                intersected_object: Some(object.clone()),
+                shape_intersection_info,
+            };
+            Some(t)
+        });
+
+        to_return
+    }
+
+    /// Go through each object in the scene and check for intersection. Objects whose
+    /// bounding box the ray misses are skipped without testing their shape.
     fn intersect_unoptimized(&self, ray: &Ray3) -> ObjectGroupIntersectionInfo {
         let mut working_ray = ray.clone();
+        let inverse_direction = inverse_direction_of(ray);
         let mut to_return = ObjectGroupIntersectionInfo {
             intersected_object: None,
             shape_intersection_info: ShapeIntersectionInfo::default(),
         };
 
-        for object in self.objects.iter() {
+        for (object, bounds) in self.objects.iter().zip(self.bounds.iter()) {
+            if bounds.hit_interval(&working_ray, &inverse_direction).is_none() { continue; }
+
             let shape_intersection_info = object.shape.intersect(&working_ray);
             
             if !shape_intersection_info.did_hit { continue; }
@@ -0,0 +1,462 @@
+//! Triangle meshes. The vertex data lives in `MeshBuffers`, which are reference counted
+//! so that several meshes (e.g. the groups of one OBJ file) can share them, and each
+//! triangle only stores indices into them.
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    utility::{
+        math::{
+            float::Float,
+            vector::{Vec3, Point3, Normal3, Color3, cross},
+            ray::Ray3,
+            aabb::Aabb,
+            orthonormal_basis::OrthonormalBasis
+        },
+        rng::RandomNumberGenerator
+    },
+    objects::{textures::traits::TextureCoordinates, bvh::Bvh},
+    config::{MIRTH_CONFIG, AccStructureKind},
+    sampler
+};
+use super::{
+    transform::Transform,
+    traits::{
+        Transformable, ShapeLike, IntersectableShape, ShapeIntersectionInfo,
+        SampleableShape, ShapeSampleResult, area_pdf_to_solid_angle, BoundedShape,
+        is_front_face
+    }
+};
+
+// E==== IMPORTS }}}1
+
+/// The texture coordinates of the corners of triangles that come without any.
+const DEFAULT_UVS: [(Float, Float); 3] = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)];
+
+/// Vertex data, in the local space of the meshes using it.
+#[derive(Debug, Default)]
+pub struct MeshBuffers {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Normal3>,
+    pub uvs: Vec<(Float, Float)>,
+    /// Linear colors, which the vertex color texture looks up.
+    pub colors: Vec<Color3>,
+}
+
+/// The indices into `MeshBuffers` of the corners of a triangle. The corners are in
+/// counterclockwise order when looking at the front of the triangle. Normals, texture
+/// coordinates and colors are either given for all three corners or not at all.
+#[derive(Clone, Debug)]
+pub struct MeshTriangle {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+    pub colors: Option<[usize; 3]>,
+}
+
+pub struct MeshInfo {
+    pub buffers: Arc<MeshBuffers>,
+    /// Each index must be in range for the buffer it refers to.
+    pub triangles: Vec<MeshTriangle>,
+    pub transform: Transform,
+}
+
+pub struct Mesh {
+    buffers: Arc<MeshBuffers>,
+    triangles: Vec<MeshTriangle>,
+    transform: Transform,
+    /// The local space bounding box of each triangle, in the same order as `triangles`.
+    bounds: Vec<Aabb>,
+    /// Built over the triangles (in local space), unless `MIRTH_CONFIG` asks for no
+    /// acceleration structure.
+    bvh: Option<Bvh>,
+    /// The world space area of the first $i+1$ triangles at index $i$, for picking a
+    /// triangle with probability proportional to its area.
+    cumulative_areas: Vec<Float>,
+}
+
+/// Where a ray hits a single triangle.
+struct TriangleHit {
+    t: Float,
+    /// The weight of each corner in the point hit.
+    barycentrics: [Float; 3],
+}
+
+impl Mesh {
+    pub fn new(info: MeshInfo) -> Self {
+        let acceleration_structure = &MIRTH_CONFIG.acceleration_structure;
+
+        let bounds: Vec<Aabb> = info.triangles.iter()
+            .map(|triangle| {
+                let corners = triangle.positions.map(|i| info.buffers.positions[i].clone());
+                Aabb::new_from_points(&corners)
+            })
+            .collect();
+
+        let bvh = match acceleration_structure.kind {
+            AccStructureKind::Nothing => None,
+            AccStructureKind::BBH => {
+                Some(Bvh::new(&bounds, &acceleration_structure.axis_selection_method))
+            },
+        };
+
+        let mut mesh = Self {
+            buffers: info.buffers,
+            triangles: info.triangles,
+            transform: info.transform,
+            bounds,
+            bvh,
+            cumulative_areas: Vec::new(),
+        };
+
+        let mut total_area = 0.0;
+        mesh.cumulative_areas = (0..mesh.triangles.len())
+            .map(|index| {
+                total_area += mesh.global_area_and_normal(index).0;
+                total_area
+            })
+            .collect();
+
+        mesh
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// The world space area of all the triangles.
+    pub fn total_area(&self) -> Float {
+        self.cumulative_areas.last().copied().unwrap_or(0.0)
+    }
+
+    /// The local space positions of the corners of a triangle.
+    fn corners(&self, index: usize) -> [&Point3; 3] {
+        self.triangles[index].positions.map(|i| &self.buffers.positions[i])
+    }
+
+    /// The area of a triangle after it has been transformed into world space, along with
+    /// its world space (geometric) normal, which points out of the front of the triangle.
+    fn global_area_and_normal(&self, index: usize) -> (Float, Normal3) {
+        let [p0, p1, p2] = self.corners(index).map(|p| self.transform.point_to_global(p));
+        let area_normal = cross(&(&p1 - &p0), &(&p2 - &p0));
+
+        (0.5 * area_normal.length(), Normal3::new_from_vec3(area_normal.normalize()))
+    }
+}
+
+// S==== INTERSECTION {{{1
+
+impl IntersectableShape for Mesh {
+    /// We intersect in local space, where the bounding boxes of the hierarchy are.
+    fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo {
+        let local_ray = self.transform.ray_to_local(ray);
+        let mut closest: Option<(usize, TriangleHit)> = None;
+
+        match &self.bvh {
+            Some(bvh) => {
+                bvh.traverse(&local_ray, |index, working_ray| {
+                    let hit = self.intersect_triangle(index, working_ray)?;
+                    let t = hit.t;
+                    closest = Some((index, hit));
+                    Some(t)
+                });
+            },
+            None => {
+                let mut working_ray = local_ray.clone();
+                for index in 0..self.triangles.len() {
+                    if !self.bounds[index].intersects(&working_ray) { continue; }
+
+                    if let Some(hit) = self.intersect_triangle(index, &working_ray) {
+                        working_ray.max_t = hit.t;
+                        closest = Some((index, hit));
+                    }
+                }
+            },
+        }
+
+        match closest {
+            Some((index, hit)) => self.intersection_info(index, &hit, ray),
+            None => ShapeIntersectionInfo::no_intersection(),
+        }
+    }
+}
+
+impl Mesh {
+    /// The watertight ray-triangle test of Woop, Benthin and Wald. We move to coordinates
+    /// in which the ray starts at the origin and points along $+z$, by translating,
+    /// permuting the axes so that $z$ is the one in which the direction is largest, and
+    /// shearing. Whether the ray hits is then a 2D question: does the triangle, projected
+    /// onto the $xy$-plane, contain the origin? This is decided by the signs of the edge
+    /// functions $e_i$, and since neighbouring triangles evaluate their shared edge in
+    /// exactly the same way, a ray can't slip between them. The edge functions divided by
+    /// their sum are the barycentric coordinates of the hit.
+    ///
+    /// The computation is done in double precision, so that the edge functions are only
+    /// zero when the ray really passes through an edge.
+    fn intersect_triangle(&self, index: usize, ray: &Ray3) -> Option<TriangleHit> {
+        let direction = [
+            ray.direction.x() as f64, ray.direction.y() as f64, ray.direction.z() as f64
+        ];
+
+        let kz = (0..3)
+            .max_by(|&a, &b| direction[a].abs().total_cmp(&direction[b].abs()))
+            .unwrap_or(2);
+        let kx = (kz + 1) % 3;
+        let ky = (kx + 1) % 3;
+
+        let shear_x = -direction[kx] / direction[kz];
+        let shear_y = -direction[ky] / direction[kz];
+        let shear_z = 1.0 / direction[kz];
+
+        let [p0, p1, p2] = self.corners(index).map(|corner| {
+            let relative = [
+                corner.x() as f64 - ray.origin.x() as f64,
+                corner.y() as f64 - ray.origin.y() as f64,
+                corner.z() as f64 - ray.origin.z() as f64,
+            ];
+            [
+                relative[kx] + shear_x * relative[kz],
+                relative[ky] + shear_y * relative[kz],
+                shear_z * relative[kz],
+            ]
+        });
+
+        let e0 = p1[0] * p2[1] - p1[1] * p2[0];
+        let e1 = p2[0] * p0[1] - p2[1] * p0[0];
+        let e2 = p0[0] * p1[1] - p0[1] * p1[0];
+
+        let any_negative = e0 < 0.0 || e1 < 0.0 || e2 < 0.0;
+        let any_positive = e0 > 0.0 || e1 > 0.0 || e2 > 0.0;
+        if any_negative && any_positive {
+            return None;
+        }
+
+        let determinant = e0 + e1 + e2;
+        if determinant == 0.0 {
+            return None;
+        }
+
+        let t = ((e0 * p0[2] + e1 * p1[2] + e2 * p2[2]) / determinant) as Float;
+        if !ray.is_in_range(t) {
+            return None;
+        }
+
+        Some(TriangleHit {
+            t,
+            barycentrics: [e0, e1, e2].map(|e| (e / determinant) as Float),
+        })
+    }
+
+    /// Interpolates the vertex data of the triangle hit, and moves it into world space.
+    fn intersection_info(&self, index: usize, hit: &TriangleHit, ray: &Ray3) -> ShapeIntersectionInfo {
+        let triangle = &self.triangles[index];
+        let [b0, b1, b2] = hit.barycentrics;
+        let [p0, p1, p2] = self.corners(index);
+        let local_point = b0 * p0 + b1 * p1 + b2 * p2;
+
+        let uvs = match &triangle.uvs {
+            Some(indices) => indices.map(|i| self.buffers.uvs[i]),
+            None => DEFAULT_UVS,
+        };
+        let u = b0 * uvs[0].0 + b1 * uvs[1].0 + b2 * uvs[2].0;
+        let v = b0 * uvs[0].1 + b1 * uvs[1].1 + b2 * uvs[2].1;
+
+        // Solve dp02 = du02 dpdu + dv02 dpdv and dp12 = du12 dpdu + dv12 dpdv.
+        let dp02 = p0 - p2;
+        let dp12 = p1 - p2;
+        let (du02, dv02) = (uvs[0].0 - uvs[2].0, uvs[0].1 - uvs[2].1);
+        let (du12, dv12) = (uvs[1].0 - uvs[2].0, uvs[1].1 - uvs[2].1);
+        let uv_determinant = du02 * dv12 - dv02 * du12;
+
+        let local_geometric_normal = cross(&dp02, &dp12).normalize();
+        let (local_dpdu, local_dpdv) = {
+            let from_uvs = (uv_determinant.abs() > 1e-9).then(|| {
+                let inverse = 1.0 / uv_determinant;
+                (
+                    inverse * (dv12 * &dp02 - dv02 * &dp12),
+                    inverse * (du02 * &dp12 - du12 * &dp02),
+                )
+            });
+
+            match from_uvs {
+                Some((dpdu, dpdv)) if cross(&dpdu, &dpdv).length() > 0.0 => (dpdu, dpdv),
+                // Degenerate texture coordinates: any tangents will do.
+                _ => {
+                    let basis = OrthonormalBasis::new_from_vector(&local_geometric_normal);
+                    (
+                        basis.vector_from_local(Vec3::new(1.0, 0.0, 0.0)),
+                        basis.vector_from_local(Vec3::new(0.0, 1.0, 0.0)),
+                    )
+                },
+            }
+        };
+
+        let geometric_normal = self.transform
+            .normal_to_global(&Normal3::new_from_vec3(local_geometric_normal))
+            .normalize();
+        let shading_normal = match &triangle.normals {
+            Some(indices) => {
+                let [n0, n1, n2] = indices.map(|i| self.buffers.normals[i].as_vec3());
+                let local_normal = Normal3::new_from_vec3(b0 * n0 + b1 * n1 + b2 * n2);
+                self.transform.normal_to_global(&local_normal).normalize()
+            },
+            None => geometric_normal.clone(),
+        };
+        // The vertex normals say which side of the surface is the outside.
+        let geometric_normal = geometric_normal.facing(shading_normal.as_vec3());
+
+        let mut texture_coordinates = TextureCoordinates::new(u, v, shading_normal.clone().into());
+        texture_coordinates.vertex_color = triangle.colors.map(|indices| {
+            let [c0, c1, c2] = indices.map(|i| &self.buffers.colors[i]);
+            b0 * c0 + b1 * c1 + b2 * c2
+        });
+        let point = self.transform.point_to_global(&local_point);
+        texture_coordinates.point = point.clone();
+        texture_coordinates.object_point = local_point;
+
+        ShapeIntersectionInfo {
+            did_hit: true,
+            point,
+            t: hit.t,
+            is_front_face: is_front_face(&ray.direction, &geometric_normal),
+            geometric_normal,
+            shading_normal,
+            dpdu: self.transform.vector_to_global(&local_dpdu),
+            dpdv: self.transform.vector_to_global(&local_dpdv),
+            texture_coordinates,
+        }
+    }
+}
+
+// E==== INTERSECTION }}}1
+
+impl SampleableShape for Mesh {
+    /// Samples uniformly with respect to the area of the whole mesh: first a triangle,
+    /// with probability proportional to its area, then a point on it.
+    fn sample_from(&self, reference: &Point3, rng: &mut RandomNumberGenerator) -> ShapeSampleResult {
+        let total_area = self.total_area();
+        let target = rng.next_float() * total_area;
+        let index = self.cumulative_areas
+            .partition_point(|&area| area <= target)
+            .min(self.triangles.len() - 1);
+
+        let barycentrics = sampler::uniform_on_triangle(rng).point;
+        let [p0, p1, p2] = self.corners(index);
+        let local_point = (1.0 - barycentrics.x() - barycentrics.y()) * p0
+            + barycentrics.x() * p1
+            + barycentrics.y() * p2;
+
+        let point = self.transform.point_to_global(&local_point);
+        let (_, surface_normal) = self.global_area_and_normal(index);
+        let pdf = area_pdf_to_solid_angle(1.0 / total_area, reference, &point, &surface_normal);
+
+        ShapeSampleResult {
+            point,
+            surface_normal,
+            pdf,
+        }
+    }
+
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        let intersection = self.intersect(&Ray3::new(reference.clone(), direction.clone()));
+        if !intersection.did_hit {
+            return 0.0;
+        }
+
+        area_pdf_to_solid_angle(
+            1.0 / self.total_area(),
+            reference,
+            &intersection.point,
+            &intersection.geometric_normal
+        )
+    }
+}
+
+impl BoundedShape for Mesh {
+    /// The union of the triangles' boxes, each transformed into world space. This is
+    /// tighter than transforming the union of their local boxes.
+    fn bounding_box(&self) -> Aabb {
+        self.bounds.iter()
+            .fold(Aabb::empty(), |acc, bounds| acc.union(&self.transform.aabb_to_global(bounds)))
+    }
+}
+
+impl Transformable for Mesh {
+    fn get_transform(&self) -> Transform {
+        self.transform.clone()
+    }
+}
+
+impl ShapeLike for Mesh {}
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::math::{float::SignCheckable, vector::dot};
+    use super::*;
+
+    /// The unit square in the $xy$-plane, split along its diagonal, with normals tilted
+    /// towards $+x$ on the right edge.
+    fn square() -> Mesh {
+        let buffers = MeshBuffers {
+            positions: vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![
+                Normal3::new(0.0, 0.0, 1.0),
+                Normal3::new(1.0, 0.0, 1.0).normalize(),
+            ],
+            uvs: vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            colors: Vec::new(),
+        };
+
+        Mesh::new(MeshInfo {
+            buffers: Arc::new(buffers),
+            triangles: vec![
+                MeshTriangle { positions: [0, 1, 2], normals: Some([0, 1, 1]), uvs: Some([0, 1, 2]), colors: None },
+                MeshTriangle { positions: [0, 2, 3], normals: Some([0, 1, 0]), uvs: Some([0, 2, 3]), colors: None },
+            ],
+            transform: Transform::default(),
+        })
+    }
+
+    #[test]
+    fn shared_edges_and_vertices_are_hit() {
+        let mesh = square();
+        let down = Vec3::new(0.0, 0.0, -1.0);
+
+        // Through the diagonal, the corner both triangles share, and then from below.
+        for origin in [Point3::new(0.5, 0.5, 1.0), Point3::new(1.0, 1.0, 1.0)] {
+            let hit = mesh.intersect(&Ray3::new(origin, down.clone()));
+            assert!(hit.did_hit);
+            assert!((hit.t - 1.0).is_zero());
+        }
+        let from_below = mesh.intersect(&Ray3::new(Point3::new(0.3, 0.6, -2.0), Vec3::new(0.0, 0.0, 1.0)));
+        assert!(from_below.did_hit);
+        assert!((from_below.t - 2.0).is_zero());
+
+        let outside = mesh.intersect(&Ray3::new(Point3::new(1.5, 0.5, 1.0), down));
+        assert!(!outside.did_hit);
+    }
+
+    #[test]
+    fn vertex_data_is_interpolated() {
+        let mesh = square();
+        let hit = mesh.intersect(&Ray3::new(Point3::new(0.75, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0)));
+        assert!(hit.did_hit);
+
+        let coordinates = &hit.texture_coordinates;
+        assert!((coordinates.u - 0.75).is_zero());
+        assert!((coordinates.v - 0.25).is_zero());
+        assert!(Vec3::are_equal(&hit.dpdu, &Vec3::new(1.0, 0.0, 0.0)));
+        assert!(Vec3::are_equal(&hit.dpdv, &Vec3::new(0.0, 1.0, 0.0)));
+
+        // Three quarters of the way from the first corner's normal to the tilted one.
+        assert!(hit.shading_normal.x() > 0.0);
+        assert!(Vec3::are_equal(hit.geometric_normal.as_vec3(), &Vec3::new(0.0, 0.0, 1.0)));
+        assert!(dot(hit.shading_normal.as_vec3(), hit.geometric_normal.as_vec3()) > 0.0);
+    }
+}
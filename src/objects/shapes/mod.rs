@@ -2,5 +2,6 @@
 pub mod traits;
 pub mod sphere;
 pub mod quad;
+pub mod mesh;
 pub mod transform;
 
@@ -2,16 +2,24 @@
 // S==== IMPORTS {{{1
 
 use crate::{
-    utility::math::{
-        float::{Float, SignCheckable},
-        vector::Vec3,
-        ray::Ray3
-    }, objects::textures::traits::TextureCoordinates, 
-     
+    utility::{
+        math::{
+            float::{Float, SignCheckable},
+            vector::{Vec3, Point3, Normal3, cross},
+            ray::Ray3,
+            aabb::Aabb
+        }, 
+        rng::RandomNumberGenerator
+    },
+    objects::textures::traits::TextureCoordinates, 
 };
 use super::{
     transform::Transform, 
-    traits::{Transformable, ShapeLike, IntersectableShape, ShapeIntersectionInfo}
+    traits::{
+        Transformable, ShapeLike, IntersectableShape, ShapeIntersectionInfo, 
+        SampleableShape, ShapeSampleResult, area_pdf_to_solid_angle, BoundedShape,
+        is_front_face
+    }
 };
 
 // E==== IMPORTS }}}1
@@ -28,10 +36,8 @@ impl IntersectableShape for Quad {
     /// space. We do not consider the origin of $r$ lying within the quad as an 
     /// intersection. So the only way $r$ could intersect the quad is if its 
     /// $z$-component was variable, i.e. the $z$-component of $d$ is nonzero. 
-    /// We can then get the time of intersection of $r$ with the plane $z=0$ by dividing
-    /// the distance from $o$ and $z=0$ (which is the absolute value of the 
-    /// $z$-component of $o$) by the unit rate of change of the $z$-component of 
-    /// $d$ (which is the $z$-component of $d$). It then suffices to check if this
+    /// We can then get the time of intersection of $r$ with the plane $z=0$ by solving
+    /// $o_z + t d_z = 0$, i.e. $t = -o_z / d_z$. It then suffices to check if this
     /// point of intersection $r(t)$ with $z=0$ lies in the the square.
     fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo {
         let transformed_ray = self.transform.ray_to_local(ray);
@@ -40,7 +46,7 @@ impl IntersectableShape for Quad {
             return ShapeIntersectionInfo::no_intersection();
         }
 
-        let t = Float::abs(transformed_ray.origin.z()) / transformed_ray.direction.z();
+        let t = -transformed_ray.origin.z() / transformed_ray.direction.z();
 
         if !ray.is_in_range(t) {
             return ShapeIntersectionInfo::no_intersection();
@@ -53,13 +59,90 @@ impl IntersectableShape for Quad {
             temp
         };
 
+        let x = intersection_with_plane.x();
+        let y = intersection_with_plane.y();
+        if x < 0.0 || x > self.width || y < 0.0 || y > self.height {
+            return ShapeIntersectionInfo::no_intersection();
+        }
+
+        // The quad is parameterized by the fraction of its width and height we are at.
+        let normal = self.transform.normal_to_global(&Normal3::new(0.0,0.0,1.0)).normalize();
+        let u = x / self.width;
+        let v = y / self.height;
+        let point = self.transform.point_to_global(&intersection_with_plane);
+        let texture_coordinates = {
+            let mut texture_coordinates = TextureCoordinates::new(u, v, normal.clone().into());
+            texture_coordinates.point = point.clone();
+            texture_coordinates.object_point = intersection_with_plane;
+            texture_coordinates
+        };
+
         ShapeIntersectionInfo {
             did_hit: true,
-            surface_normal: self.transform.vector_to_global(&Vec3::new(0.0,0.0,1.0)),
+            geometric_normal: normal.clone(),
+            is_front_face: is_front_face(&ray.direction, &normal),
+            shading_normal: normal.clone(),
+            dpdu: self.transform.vector_to_global(&Vec3::new(self.width, 0.0, 0.0)),
+            dpdv: self.transform.vector_to_global(&Vec3::new(0.0, self.height, 0.0)),
             t,
-            point: self.transform.point_to_global(&intersection_with_plane),
-            texture_coordinates: TextureCoordinates::default(),
+            point,
+            texture_coordinates,
+        }
+    }
+}
+
+impl Quad {
+    /// The area of the quad after it has been transformed into world space, along with
+    /// its world space normal.
+    fn global_area_and_normal(&self) -> (Float, Normal3) {
+        let edge_u = self.transform.vector_to_global(&Vec3::new(self.width, 0.0, 0.0));
+        let edge_v = self.transform.vector_to_global(&Vec3::new(0.0, self.height, 0.0));
+        let area_normal = cross(&edge_u, &edge_v);
+
+        (area_normal.length(), Normal3::new_from_vec3(area_normal.normalize()))
+    }
+}
+
+impl SampleableShape for Quad {
+    /// Samples uniformly with respect to the area of the quad.
+    fn sample_from(&self, reference: &Point3, rng: &mut RandomNumberGenerator) -> ShapeSampleResult {
+        let local_point = Point3::new(
+            rng.next_float() * self.width,
+            rng.next_float() * self.height,
+            0.0
+        );
+        let point = self.transform.point_to_global(&local_point);
+        let (area, surface_normal) = self.global_area_and_normal();
+        let pdf = area_pdf_to_solid_angle(1.0 / area, reference, &point, &surface_normal);
+
+        ShapeSampleResult {
+            point,
+            surface_normal,
+            pdf,
+        }
+    }
+
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        let intersection = self.intersect(&Ray3::new(reference.clone(), direction.clone()));
+        if !intersection.did_hit {
+            return 0.0;
         }
+
+        let (area, surface_normal) = self.global_area_and_normal();
+        area_pdf_to_solid_angle(1.0 / area, reference, &intersection.point, &surface_normal)
+    }
+}
+
+impl BoundedShape for Quad {
+    /// In local space the quad is its own (flat) bounding box, so transforming that box
+    /// gives the tightest box around the transformed quad.
+    fn bounding_box(&self) -> Aabb {
+        let local_bounds = Aabb::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(self.width, self.height, 0.0)
+        );
+
+        self.transform.aabb_to_global(&local_bounds)
     }
 }
 
@@ -71,3 +154,58 @@ impl Transformable for Quad {
 
 impl ShapeLike for Quad {}
 
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::math::{vector::Point3, ray::RayDifferentials};
+    use super::*;
+
+    #[test]
+    fn intersect_only_within_bounds() {
+        let quad = Quad {
+            width: 2.0,
+            height: 1.0,
+            transform: Transform::default(),
+        };
+
+        // Ray: (1,0.5,1) + t(0,0,-1)        -- hits (1,0.5,0) at t=1
+        let r1 = Ray3::new(Point3::new(1.0, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let r1_hitinfo = quad.intersect(&r1);
+        assert!(r1_hitinfo.did_hit);
+        assert!((r1_hitinfo.t - 1.0).is_zero());
+        assert!(Point3::are_equal(&r1_hitinfo.point, &Point3::new(1.0, 0.5, 0.0)));
+
+        // Ray: (1,0.5,-1) + t(0,0,-1)       -- moving away from the plane
+        let r2 = Ray3::new(Point3::new(1.0, 0.5, -1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!quad.intersect(&r2).did_hit);
+
+        // Ray: (3,0.5,1) + t(0,0,-1)        -- hits the plane outside of the quad
+        let r3 = Ray3::new(Point3::new(3.0, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!quad.intersect(&r3).did_hit);
+    }
+
+    #[test]
+    fn texture_derivatives_from_ray_differentials() {
+        let quad = Quad {
+            width: 2.0,
+            height: 1.0,
+            transform: Transform::default(),
+        };
+
+        // Rays from a camera at (1,0.5,1) looking down, with neighboring pixels 0.01 
+        // apart at unit distance, so 0.01 apart on the quad.
+        let mut ray = Ray3::new(Point3::new(1.0, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        ray.differentials = Some(RayDifferentials {
+            rx_origin: ray.origin.clone(),
+            rx_direction: Vec3::new(0.01, 0.0, -1.0),
+            ry_origin: ray.origin.clone(),
+            ry_direction: Vec3::new(0.0, 0.01, -1.0),
+        });
+
+        let mut hit = quad.intersect(&ray);
+        hit.compute_texture_derivatives(&ray);
+        let coordinates = &hit.texture_coordinates;
+        assert!((coordinates.dudx - 0.005).abs() < 1e-5 && coordinates.dvdx.abs() < 1e-5);
+        assert!((coordinates.dvdy - 0.01).abs() < 1e-5 && coordinates.dudy.abs() < 1e-5);
+    }
+}
@@ -1,13 +1,26 @@
 
 // S==== IMPORTS {{{1
 
-use crate::utility::math::{
-    vector::{Point3, dot, Vec3}, 
-    ray::Ray3, 
-    float::{Float, SignCheckable}
+use crate::{
+    utility::{
+        math::{
+            vector::{Point3, dot, Vec3, Normal3}, 
+            ray::Ray3, 
+            float::{Float, SignCheckable, FloatConstants},
+            orthonormal_basis::OrthonormalBasis,
+            aabb::Aabb
+        },
+        rng::RandomNumberGenerator
+    },
+    objects::textures::traits::TextureCoordinates,
+    sampler
 };
 use super::{
-    traits::{ShapeIntersectionInfo, IntersectableShape, Transformable, ShapeLike}, 
+    traits::{
+        ShapeIntersectionInfo, IntersectableShape, Transformable, ShapeLike, 
+        SampleableShape, ShapeSampleResult, area_pdf_to_solid_angle, BoundedShape, 
+        is_front_face
+    }, 
     transform::{Transform, self}
 };
 
@@ -86,22 +99,178 @@ impl IntersectableShape for Sphere { // {{{1
 
         // Collect all calculations into return struct
 
-        to_return.did_hit = true;
-        to_return.point = {
-            // Project hit point to sphere surface to account for floating point errors
+        // Project hit point to sphere surface to account for floating point errors
+        let local_hitpoint: Point3 = {
             let pre_local_hitpoint = local_ray.eval(t);
-            let local_hitpoint: Point3 = 
-                &self.center + (pre_local_hitpoint - &self.center).normalize_to(self.radius);
+            &self.center + (pre_local_hitpoint - &self.center).normalize_to(self.radius)
+        };
 
-            // Convert from local coordinates 
-            self.transform.point_to_global(&local_hitpoint)
+        let normal = {
+            let local_normal = Normal3::new_from_vec3((&local_hitpoint - &self.center) / self.radius);
+            self.transform.normal_to_global(&local_normal).normalize()
         };
+        let (u, v, local_dpdu, local_dpdv) = self.parameterization_at(&local_hitpoint);
+
+        to_return.did_hit = true;
+        to_return.point = self.transform.point_to_global(&local_hitpoint);
+        to_return.geometric_normal = normal.clone();
+        to_return.is_front_face = is_front_face(&ray.direction, &normal);
+        to_return.shading_normal = normal.clone();
+        to_return.dpdu = self.transform.vector_to_global(&local_dpdu);
+        to_return.dpdv = self.transform.vector_to_global(&local_dpdv);
+        to_return.texture_coordinates = TextureCoordinates::new(u, v, normal.into());
+        to_return.texture_coordinates.point = to_return.point.clone();
+        to_return.texture_coordinates.object_point = local_hitpoint;
         to_return.t = t;
 
         return to_return;
     }
 } // }}}1
 
+impl Sphere {
+    /// We parameterize the sphere by spherical coordinates about its center: $u = \phi / 2\pi$,
+    /// where $\phi \in [0, 2\pi)$ is the angle around the $z$-axis measured from the 
+    /// $x$-axis, and $v = \theta / \pi$, where $\theta \in [0, \pi]$ is the angle from the
+    /// $+z$ pole. So with $p - c = (x, y, z)$,
+    /// $$\frac{\partial p}{\partial u} = 2\pi (-y, x, 0), \quad
+    ///   \frac{\partial p}{\partial v} = \pi (z \cos\phi, z \sin\phi, -r \sin\theta).$$
+    /// Returns $(u, v, \partial p / \partial u, \partial p / \partial v)$, all in local 
+    /// space, for a point on the (local) sphere.
+    fn parameterization_at(&self, local_point: &Point3) -> (Float, Float, Vec3, Vec3) {
+        let p = local_point - &self.center;
+
+        let phi = {
+            let phi = Float::atan2(p.y(), p.x());
+            if phi < 0.0 { phi + 2.0 * Float::get_pi() } else { phi }
+        };
+        let theta = Float::acos(Float::clamp(p.z() / self.radius, -1.0, 1.0));
+
+        let u = phi / (2.0 * Float::get_pi());
+        let v = theta / Float::get_pi();
+
+        let dpdu = (2.0 * Float::get_pi()) * Vec3::new(-p.y(), p.x(), 0.0);
+        let dpdv = Float::get_pi() * Vec3::new(
+            p.z() * Float::cos(phi),
+            p.z() * Float::sin(phi),
+            -self.radius * Float::sin(theta)
+        );
+
+        (u, v, dpdu, dpdv)
+    }
+}
+
+impl Sphere {
+    /// The center and radius of the sphere in world space. This is only meaningful when
+    /// `transform` takes spheres to spheres, i.e. scales uniformly.
+    fn global_center_and_radius(&self) -> (Point3, Float) {
+        let center = self.transform.point_to_global(&self.center);
+        let radius = self.transform.vector_to_global(&Vec3::new(self.radius, 0.0, 0.0)).length();
+
+        (center, radius)
+    }
+
+    /// The cosine of the half-angle of the cone, with apex at `reference`, that the 
+    /// sphere subtends. `None` if `reference` is inside the sphere.
+    fn cos_theta_max_from(&self, reference: &Point3) -> Option<Float> {
+        let (center, radius) = self.global_center_and_radius();
+        let to_center = &center - reference;
+        let distance_squared = dot(&to_center, &to_center);
+
+        if distance_squared <= radius * radius {
+            return None;
+        }
+
+        let sin_theta_max_squared = (radius * radius) / distance_squared;
+        Some(Float::sqrt(Float::max(0.0, 1.0 - sin_theta_max_squared)))
+    }
+}
+
+impl SampleableShape for Sphere {
+    /// From outside the sphere we sample uniformly within the cone of directions that the
+    /// sphere subtends, so that every sample is visible. From inside, we fall back to 
+    /// sampling uniformly with respect to area. Both assume that the sphere's transform
+    /// scales uniformly.
+    fn sample_from(&self, reference: &Point3, rng: &mut RandomNumberGenerator) -> ShapeSampleResult {
+        let (center, radius) = self.global_center_and_radius();
+
+        let cos_theta_max = match self.cos_theta_max_from(reference) {
+            Some(c) => c,
+            None => {
+                let sample = sampler::uniform_on_2sphere(rng);
+                let point = &center + radius * &sample.point;
+                let area = 4.0 * Float::get_pi() * radius * radius;
+                let surface_normal = Normal3::new_from_vec3(sample.point);
+                let pdf = area_pdf_to_solid_angle(1.0 / area, reference, &point, &surface_normal);
+
+                return ShapeSampleResult {
+                    point,
+                    surface_normal,
+                    pdf,
+                };
+            }
+        };
+
+        let to_center = &center - reference;
+        let distance = to_center.length();
+        let sample = sampler::uniform_in_2sphere_cone(rng, cos_theta_max);
+        let direction = OrthonormalBasis::new_from_vector(&to_center).vector_from_local(sample.point.clone());
+
+        // The distance along `direction` to the near side of the sphere. By construction 
+        // the discriminant is nonnegative, up to floating point error.
+        let cos_theta = sample.point.z();
+        let sin_theta_squared = 1.0 - cos_theta * cos_theta;
+        let t = distance * cos_theta 
+            - Float::sqrt(Float::max(0.0, radius * radius - distance * distance * sin_theta_squared));
+
+        let point = reference + t * &direction;
+        let surface_normal = Normal3::new_from_vec3((&point - &center).normalize());
+
+        ShapeSampleResult {
+            point,
+            surface_normal,
+            pdf: sample.pdf,
+        }
+    }
+
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float {
+        let intersection = self.intersect(&Ray3::new(reference.clone(), direction.clone()));
+        if !intersection.did_hit {
+            return 0.0;
+        }
+
+        match self.cos_theta_max_from(reference) {
+            Some(cos_theta_max) => 1.0 / (2.0 * Float::get_pi() * (1.0 - cos_theta_max)),
+            None => {
+                let (_, radius) = self.global_center_and_radius();
+                let area = 4.0 * Float::get_pi() * radius * radius;
+                area_pdf_to_solid_angle(1.0 / area, reference, &intersection.point, &intersection.geometric_normal)
+            }
+        }
+    }
+}
+
+impl BoundedShape for Sphere {
+    /// The transformed sphere is an ellipsoid. Writing the transform as $x \mapsto Mx + b$,
+    /// its points are $M(c + ru) + b$ for unit vectors $u$, and the largest value the 
+    /// $i$-th coordinate takes is $(Mc + b)_i + r \lVert M_i \rVert$, where $M_i$ is 
+    /// the $i$-th row of $M$. So the bounds are exact even under rotation and 
+    /// non-uniform scale.
+    fn bounding_box(&self) -> Aabb {
+        let center = self.transform.point_to_global(&self.center);
+        let matrix = self.transform.local_to_global_matrix();
+
+        let half_extent = |i: usize| -> Float {
+            let row_norm_squared: Float = (0..3)
+                .map(|j| matrix.element(i, j) * matrix.element(i, j))
+                .sum();
+            self.radius * Float::sqrt(row_norm_squared)
+        };
+        let half_extents = Vec3::new(half_extent(0), half_extent(1), half_extent(2));
+
+        Aabb::new(&center - &half_extents, &center + &half_extents)
+    }
+}
+
 impl Transformable for Sphere {
     fn get_transform(&self) -> Transform {
         self.transform.clone()
@@ -110,54 +279,131 @@ impl Transformable for Sphere {
 
 impl ShapeLike for Sphere {}
 
-// #[cfg(test)] // {{{1
-// mod tests {
-//     use crate::utility::math::vector::Vec3;
-//
-//     use super::*;
-//
-//     #[test]
-//     fn no_transform_intersection() {
-//         // Sphere: radius 2, centered at (0,0,3)
-//         let sphere = Sphere::new(Point3::new(0.0,0.0,3.0), 2.0);
-//
-//         // Ray 1: (0,0,0) + t(0,0,1)            -- h1=(0,0,1), h2=(0,0,5)
-//         let mut r1 = Ray3::new(
-//             Point3::new(0.0, 0.0, 0.0), 
-//             Vec3::new(0.0, 0.0, 1.0)
-//         );
-//         let mut r1_hitinfo = sphere.intersect(&r1);
-//         assert!(
-//             Point3::are_equal(&r1_hitinfo.point, &Vec3::new(0.0, 0.0, 1.0))
-//         );
-//
-//         r1.min_t = 1.5;
-//         r1_hitinfo = sphere.intersect(&r1);
-//         assert!(
-//             Point3::are_equal(&r1_hitinfo.point, &Vec3::new(0.0, 0.0, 5.0))
-//         );
-//
-//         // Ray 2: (2,0,0) + t(0,0,0.3)          -- h1=(2,0,3), h2=n/a
-//         let mut r2 = Ray3::new(
-//             Point3::new(2.0, 0.0, 0.0),
-//             Vec3::new(0.0, 0.0, 0.3)
-//         );
-//         let mut r2_hitinfo = sphere.intersect(&r2);
-//         assert!(
-//             Point3::are_equal(&r2_hitinfo.point, &Vec3::new(2.0, 0.0, 3.0)),
-//         );
-//
-//         r2.min_t = 10.1;
-//         r2_hitinfo = sphere.intersect(&r2);
-//         assert!(r2_hitinfo.did_hit == false);
-//
-//         // Ray 3: (0,0,0) + t(5,5,5)            -- h1=n/a, h2=n/a
-//         let r3 = Ray3::new(
-//             Point3::new(0.0, 0.0, 0.0),
-//             Vec3::new(-1.0, -1.0, -1.0)
-//         );
-//         let r3_hitinfo = sphere.intersect(&r3);
-//         assert!(r3_hitinfo.did_hit == false);
-//     }
-// } // }}}1
+// S==== TESTS {{{1
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::math::{
+        matrix::{Matrix4, Matrix4TransformKind, Matrix4AxisRotationInfo},
+        angle::{Angle, AngleUnits}
+    };
+    use super::*;
+
+    #[test]
+    fn cone_samples_lie_on_sphere() {
+        let sphere = Sphere::new(SphereInfo {
+            center: Point3::new(0.0, 0.0, 5.0),
+            radius: 1.0,
+            transform: Transform::default(),
+        });
+        let reference = Point3::origin();
+        let mut rng = RandomNumberGenerator::from_seed(1);
+
+        for _ in 0..100 {
+            let sample = sphere.sample_from(&reference, &mut rng);
+
+            let distance_to_center = (&sample.point - &Point3::new(0.0, 0.0, 5.0)).length();
+            assert!((distance_to_center - 1.0).abs() < 0.001);
+
+            let direction = (&sample.point - &reference).normalize();
+            let pdf = sphere.pdf_from(&reference, &direction);
+            assert!((pdf - sample.pdf).abs() < 0.001 * sample.pdf);
+        }
+    }
+
+    #[test]
+    fn bounding_box_is_tight_under_rotation_and_scale() {
+        let matrix = Matrix4::new_from_sequence(&vec![
+            Matrix4TransformKind::Scale(Vec3::new(3.0, 1.0, 1.0)),
+            Matrix4TransformKind::AxisRotation(Matrix4AxisRotationInfo {
+                axis: Vec3::new(0.0, 0.0, 1.0),
+                angle: Angle { amount: 90.0, units: AngleUnits::Degrees },
+            }),
+        ]);
+        let sphere = Sphere::new(SphereInfo {
+            center: Point3::origin(),
+            radius: 2.0,
+            transform: Transform::new_from_matrix(&matrix),
+        });
+
+        // Whichever order the two are applied in, the ellipsoid's long axis (of 
+        // half-length 6) lies along either x or y, and its other axes have half-length 2.
+        let half_extents = 0.5 * sphere.bounding_box().diagonal();
+        let mut sorted = [half_extents.x(), half_extents.y(), half_extents.z()];
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert!((sorted[0] - 2.0).abs() < 0.001);
+        assert!((sorted[1] - 2.0).abs() < 0.001);
+        assert!((sorted[2] - 6.0).abs() < 0.001);
+        assert!((half_extents.z() - 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn tangents_match_parameterization() {
+        let sphere = Sphere::new(SphereInfo {
+            center: Point3::new(1.0, 2.0, 3.0),
+            radius: 2.0,
+            transform: Transform::new_from_matrix(&Matrix4::new_from_sequence(&vec![
+                Matrix4TransformKind::Scale(Vec3::new(1.0, 2.0, 3.0)),
+                Matrix4TransformKind::AxisRotation(Matrix4AxisRotationInfo {
+                    axis: Vec3::new(0.0, 1.0, 0.0),
+                    angle: Angle { amount: 40.0, units: AngleUnits::Degrees },
+                }),
+            ])),
+        });
+        // Aim at the point (1, 2, 3) + 2 (cos 0.3, 0, sin 0.3) of the local sphere.
+        let target = sphere.transform.point_to_global(
+            &Point3::new(1.0 + 2.0 * Float::cos(0.3), 2.0, 3.0 + 2.0 * Float::sin(0.3))
+        );
+        let origin = Point3::new(20.0, 5.0, 30.0);
+        let hit = sphere.intersect(&Ray3::new(origin.clone(), &target - &origin));
+        assert!(hit.did_hit);
+
+        // The tangents lie in the surface (which needs the normal to be transformed
+        // correctly, as the scale is non-uniform), and nudging (u, v) moves the point
+        // along them.
+        let normal = hit.geometric_normal.as_vec3();
+        assert!(dot(&hit.dpdu, normal).abs() < 0.001 * hit.dpdu.length());
+        assert!(dot(&hit.dpdv, normal).abs() < 0.001 * hit.dpdv.length());
+
+        let point_at = |u: Float, v: Float| {
+            let (phi, theta) = (2.0 * Float::get_pi() * u, Float::get_pi() * v);
+            let local = Point3::new(
+                1.0 + 2.0 * Float::sin(theta) * Float::cos(phi),
+                2.0 + 2.0 * Float::sin(theta) * Float::sin(phi),
+                3.0 + 2.0 * Float::cos(theta)
+            );
+            sphere.transform.point_to_global(&local)
+        };
+        let (u, v) = (hit.texture_coordinates.u, hit.texture_coordinates.v);
+        assert!((&point_at(u, v) - &hit.point).length() < 0.001);
+
+        let h = 0.001;
+        let du_step = &point_at(u + h, v) - &hit.point;
+        let dv_step = &point_at(u, v + h) - &hit.point;
+        assert!((du_step - h * &hit.dpdu).length() < 0.01 * h * hit.dpdu.length());
+        assert!((dv_step - h * &hit.dpdv).length() < 0.01 * h * hit.dpdv.length());
+    }
+
+    #[test]
+    fn rays_from_inside_hit_the_back_face() {
+        let sphere = Sphere::new(SphereInfo {
+            center: Point3::origin(),
+            radius: 2.0,
+            transform: Transform::default(),
+        });
+        let direction = Vec3::new(0.0, 0.0, 1.0);
+
+        let from_outside = sphere.intersect(&Ray3::new(Point3::new(0.0, 0.0, -5.0), direction.clone()));
+        assert!(from_outside.did_hit && from_outside.is_front_face);
+        assert!((from_outside.t - 3.0).abs() < 0.001);
+
+        // Only the far root is in front of the origin.
+        let from_inside = sphere.intersect(&Ray3::new(Point3::origin(), direction));
+        assert!(from_inside.did_hit && !from_inside.is_front_face);
+        assert!((from_inside.t - 2.0).abs() < 0.001);
+        assert!(from_inside.geometric_normal.z() > 0.0);
+    }
+}
 
+// E==== TESTS }}}1
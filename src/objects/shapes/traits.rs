@@ -2,9 +2,13 @@
 
 use crate::{
     objects::textures::traits::TextureCoordinates, 
-    utility::math::{
-        vector::{Point3, Vec3}, 
-        float::Float, ray::Ray3}
+    utility::{
+        math::{
+            vector::{Point3, Vec3, Normal3, dot}, 
+            float::Float, ray::Ray3, aabb::Aabb
+        },
+        rng::RandomNumberGenerator
+    }
 };
 use super::transform::Transform;
 
@@ -14,11 +18,27 @@ pub trait Transformable {
     fn get_transform(&self) -> Transform;
 }
 
+/// Where a ray hit a shape, along with the local geometry of the surface there. All
+/// vectors are in world space.
 pub struct ShapeIntersectionInfo {
     pub did_hit: bool,
     pub point: Point3,
     pub t: Float,
-    pub surface_normal: Vec3,
+    /// The unit normal of the actual surface. Offsetting rays leaving the surface and
+    /// deciding which side of it a direction lies on should use this normal.
+    pub geometric_normal: Normal3,
+    /// Whether the ray arrived from the side of the surface that `geometric_normal` 
+    /// points towards, which for closed shapes is the outside.
+    pub is_front_face: bool,
+    /// The unit normal that materials should shade with. It may differ from the 
+    /// geometric normal, e.g. for meshes with interpolated normals.
+    pub shading_normal: Normal3,
+    /// The partial derivatives of the point with respect to the surface parameters $u$ 
+    /// and $v$ (those in `texture_coordinates`). They need not be unit length or 
+    /// orthogonal, and may vanish where the parameterization is degenerate (like at the
+    /// poles of a sphere).
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     pub texture_coordinates: TextureCoordinates,
 }
 
@@ -28,7 +48,11 @@ impl Default for ShapeIntersectionInfo {
             did_hit: false,
             point: Point3::default(),
             t: Float::INFINITY,
-            surface_normal: Vec3::new(0.0,0.0,0.0),
+            geometric_normal: Normal3::new(0.0,0.0,0.0),
+            is_front_face: false,
+            shading_normal: Normal3::new(0.0,0.0,0.0),
+            dpdu: Vec3::new(0.0,0.0,0.0),
+            dpdv: Vec3::new(0.0,0.0,0.0),
             texture_coordinates: TextureCoordinates::default(),
         }
     }
@@ -41,11 +65,118 @@ impl ShapeIntersectionInfo {
             ..Default::default()
         }
     }
+
+    /// Fills in the screen space derivatives of the texture coordinates, if `ray` (the 
+    /// ray that hit) carries differentials. Following pbrt, we approximate the surface
+    /// by its tangent plane, intersect the neighboring rays with it, and express the 
+    /// offsets of those intersections from `point` in terms of `dpdu` and `dpdv`.
+    pub fn compute_texture_derivatives(&mut self, ray: &Ray3) {
+        let differentials = match &ray.differentials {
+            Some(differentials) => differentials,
+            None => { return; }
+        };
+
+        let normal = self.geometric_normal.as_vec3();
+        let plane_offset = dot(normal, &self.point);
+        let hit_tangent_plane = |origin: &Point3, direction: &Vec3| {
+            let denominator = dot(normal, direction);
+            if denominator == 0.0 {
+                return None;
+            }
+            let t = (plane_offset - dot(normal, origin)) / denominator;
+            Some(origin + t * direction)
+        };
+        let (px, py) = match (
+            hit_tangent_plane(&differentials.rx_origin, &differentials.rx_direction),
+            hit_tangent_plane(&differentials.ry_origin, &differentials.ry_direction),
+        ) {
+            (Some(px), Some(py)) => (px, py),
+            _ => { return; }
+        };
+        let dpdx = px - &self.point;
+        let dpdy = py - &self.point;
+
+        // The offsets are in the tangent plane, so two of the three equations 
+        // $dp = du \, dp/du + dv \, dp/dv$ suffice. We drop the one along the axis the 
+        // normal is closest to, which is the least well conditioned.
+        let (i, j) = if normal.x().abs() > normal.y().abs() && normal.x().abs() > normal.z().abs() {
+            (1, 2)
+        } else if normal.y().abs() > normal.z().abs() {
+            (0, 2)
+        } else {
+            (0, 1)
+        };
+        let (a, b) = (self.dpdu.component(i), self.dpdv.component(i));
+        let (c, d) = (self.dpdu.component(j), self.dpdv.component(j));
+        let determinant = a * d - b * c;
+        if determinant.abs() < 1.0e-12 {
+            return;
+        }
+        let solve = |dp: &Vec3| {
+            let (e, f) = (dp.component(i), dp.component(j));
+            ((d * e - b * f) / determinant, (a * f - c * e) / determinant)
+        };
+
+        let coordinates = &mut self.texture_coordinates;
+        (coordinates.dudx, coordinates.dvdx) = solve(&dpdx);
+        (coordinates.dudy, coordinates.dvdy) = solve(&dpdy);
+    }
+}
+
+/// Whether a ray in `direction` hits the front of a surface with normal `geometric_normal`.
+pub fn is_front_face(direction: &Vec3, geometric_normal: &Normal3) -> bool {
+    dot(direction, geometric_normal.as_vec3()) < 0.0
 }
 
 pub trait IntersectableShape {
     fn intersect(&self, ray: &Ray3) -> ShapeIntersectionInfo;
 }
 
-pub trait ShapeLike: IntersectableShape + Transformable {}
+/// A point sampled on the surface of a shape, as seen from some reference point.
+pub struct ShapeSampleResult {
+    pub point: Point3,
+    pub surface_normal: Normal3,
+    /// The density with respect to solid angle, as measured from the reference point.
+    pub pdf: Float,
+}
+
+/// Shapes that can be sampled directly, which is what lets emissive shapes be used as
+/// lights that we aim rays at.
+pub trait SampleableShape {
+    /// Samples a point on the shape that is (ideally) visible from `reference`.
+    fn sample_from(&self, reference: &Point3, rng: &mut RandomNumberGenerator) -> ShapeSampleResult;
+
+    /// The density, with respect to solid angle, with which `sample_from()` would produce
+    /// the first point of the shape hit by the ray from `reference` in `direction`. This 
+    /// is zero if that ray misses the shape.
+    fn pdf_from(&self, reference: &Point3, direction: &Vec3) -> Float;
+}
+
+/// Converts a density with respect to area on a surface into a density with respect to 
+/// solid angle as seen from `reference`.
+pub fn area_pdf_to_solid_angle(
+    area_pdf: Float, 
+    reference: &Point3, 
+    point: &Point3, 
+    surface_normal: &Normal3
+) -> Float {
+    let to_point = point - reference;
+    let distance_squared = dot(&to_point, &to_point);
+    let cos_theta = Float::abs(dot(surface_normal.as_vec3(), &to_point.normalize()));
+
+    if cos_theta == 0.0 {
+        return 0.0;
+    }
+
+    area_pdf * distance_squared / cos_theta
+}
+
+/// Shapes that occupy a bounded region of space.
+pub trait BoundedShape {
+    /// A box, in world space, containing the whole shape.
+    fn bounding_box(&self) -> Aabb;
+}
+
+/// Shapes are shared between the render threads, hence `Send + Sync`.
+pub trait ShapeLike: IntersectableShape + Transformable + SampleableShape + BoundedShape + Send + Sync {}
 
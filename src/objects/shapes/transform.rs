@@ -4,10 +4,11 @@ use std::fmt::format;
 use serde::Deserialize;
 use crate::utility::math::{
     matrix::{Matrix4, Matrix4AxisRotationInfo, Matrix4TransformKind}, 
-    vector::{Point3, Vec3, cross}, 
-    ray::Ray3, 
+    vector::{Point3, Vec3, Normal3, cross}, 
+    ray::{Ray3, RayDifferentials}, 
     float::Float, 
-    angle::{Angle, AngleUnits}
+    angle::{Angle, AngleUnits},
+    aabb::Aabb
 };
 
 /// Conceptually, this struct is used to move between local and global coordinates.
@@ -39,10 +40,22 @@ impl Transform {
         self.inverse_matrix.transform_vector(vector)
     }
 
+    /// If $M$ takes vectors to local coordinates, normals go by $(M^{-1})^T$. The result
+    /// is generally not of unit length.
+    pub fn normal_to_local(&self, normal: &Normal3) -> Normal3 {
+        Normal3::new_from_vec3(self.matrix.transform_vector_by_transpose(normal.as_vec3()))
+    }
+
     pub fn ray_to_local(&self, ray: &Ray3) -> Ray3 {
         let mut to_return: Ray3 = ray.clone();
         to_return.origin = self.point_to_local(&ray.origin);
         to_return.direction = self.vector_to_local(&ray.direction);
+        to_return.differentials = ray.differentials.as_ref().map(|d| RayDifferentials {
+            rx_origin: self.point_to_local(&d.rx_origin),
+            rx_direction: self.vector_to_local(&d.rx_direction),
+            ry_origin: self.point_to_local(&d.ry_origin),
+            ry_direction: self.vector_to_local(&d.ry_direction),
+        });
 
         to_return
     }
@@ -55,13 +68,54 @@ impl Transform {
         self.matrix.transform_vector(vector)
     }
 
+    /// If $M$ takes vectors to global coordinates, normals go by $(M^{-1})^T$. This keeps
+    /// them perpendicular to the transformed surface, even under non-uniform scaling. The 
+    /// result is generally not of unit length.
+    pub fn normal_to_global(&self, normal: &Normal3) -> Normal3 {
+        Normal3::new_from_vec3(self.inverse_matrix.transform_vector_by_transpose(normal.as_vec3()))
+    }
+
     pub fn ray_to_global(&self, ray: &Ray3) -> Ray3 {
         let mut to_return: Ray3 = ray.clone();
         to_return.origin = self.point_to_global(&ray.origin);
         to_return.direction = self.vector_to_global(&ray.direction);
+        to_return.differentials = ray.differentials.as_ref().map(|d| RayDifferentials {
+            rx_origin: self.point_to_global(&d.rx_origin),
+            rx_direction: self.vector_to_global(&d.rx_direction),
+            ry_origin: self.point_to_global(&d.ry_origin),
+            ry_direction: self.vector_to_global(&d.ry_direction),
+        });
 
         to_return
     }
+
+    /// The smallest axis-aligned box containing the image of `aabb` in global 
+    /// coordinates. Each global coordinate is the translation plus a sum of terms, each
+    /// depending on a single local coordinate, so it suffices to minimize and maximize
+    /// each term separately (this is Arvo's method).
+    pub fn aabb_to_global(&self, aabb: &Aabb) -> Aabb {
+        let mut min = [0.0 as Float; 3];
+        let mut max = [0.0 as Float; 3];
+
+        for i in 0..3 {
+            min[i] = self.matrix.element(i, 3);
+            max[i] = self.matrix.element(i, 3);
+
+            for j in 0..3 {
+                let a = self.matrix.element(i, j) * aabb.min.component(j);
+                let b = self.matrix.element(i, j) * aabb.max.component(j);
+                min[i] += Float::min(a, b);
+                max[i] += Float::max(a, b);
+            }
+        }
+
+        Aabb::new(Point3::new(min[0], min[1], min[2]), Point3::new(max[0], max[1], max[2]))
+    }
+
+    /// The matrix taking local coordinates to global coordinates.
+    pub fn local_to_global_matrix(&self) -> &Matrix4 {
+        &self.matrix
+    }
 }
 
 // E==== TRANSFORMING OBJECTS }}}1
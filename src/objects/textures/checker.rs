@@ -0,0 +1,101 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    light::Spectrum,
+    utility::math::{ray::Ray3, float::Float}
+};
+use super::traits::{TextureLike, TextureCoordinates};
+
+// E==== IMPORTS }}}1
+
+/// Where the squares of a checkerboard are laid out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CheckerSpace {
+    /// Squares in the texture coordinates, `scale` of them across $[0, 1]$ each way.
+    Uv,
+    /// Cubes of side $1 / $`scale` in the local coordinates of the shape, so that the
+    /// pattern runs through it like veins through stone rather than being stretched 
+    /// over its surface.
+    Object,
+}
+
+/// Alternates between two textures, like the squares of a checkerboard.
+#[derive(Debug)]
+pub struct CheckerTexture {
+    even: Arc<dyn TextureLike>,
+    odd: Arc<dyn TextureLike>,
+    scale: Float,
+    space: CheckerSpace,
+}
+
+impl TextureLike for CheckerTexture {
+    fn value_at(&self, incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        let cell_sum: i64 = match self.space {
+            CheckerSpace::Uv => [coordinate.u, coordinate.v]
+                .iter()
+                .map(|c| (self.scale * c).floor() as i64)
+                .sum(),
+            CheckerSpace::Object => {
+                let point = &coordinate.object_point;
+                [point.x(), point.y(), point.z()]
+                    .iter()
+                    .map(|c| (self.scale * c).floor() as i64)
+                    .sum()
+            },
+        };
+
+        if cell_sum.rem_euclid(2) == 0 {
+            self.even.value_at(incoming_ray, coordinate)
+        } else {
+            self.odd.value_at(incoming_ray, coordinate)
+        }
+    }
+}
+
+impl CheckerTexture {
+    pub fn new(
+        even: Arc<dyn TextureLike>, 
+        odd: Arc<dyn TextureLike>, 
+        scale: Float, 
+        space: CheckerSpace
+    ) -> Self {
+        Self {
+            even,
+            odd,
+            scale,
+            space,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        objects::textures::constant::ConstantTexture,
+        utility::math::vector::{Color3, Point3, Vec3}
+    };
+    use super::*;
+
+    #[test]
+    fn squares_alternate() {
+        let white = Arc::new(ConstantTexture::new_from_rgb(Color3::new(1.0, 1.0, 1.0)));
+        let black = Arc::new(ConstantTexture::new_from_rgb(Color3::new(0.0, 0.0, 0.0)));
+        let value = |texture: &CheckerTexture, u: Float, v: Float, object_point: Point3| {
+            let mut coordinates = TextureCoordinates::new(u, v, Vec3::new(0.0, 0.0, 1.0));
+            coordinates.object_point = object_point;
+            texture.value_at(&Ray3::default(), &coordinates).x()
+        };
+
+        let uv = CheckerTexture::new(white.clone(), black.clone(), 2.0, CheckerSpace::Uv);
+        assert_eq!(value(&uv, 0.25, 0.25, Point3::origin()), 1.0);
+        assert_eq!(value(&uv, 0.75, 0.25, Point3::origin()), 0.0);
+        assert_eq!(value(&uv, 0.75, 0.75, Point3::origin()), 1.0);
+
+        let object = CheckerTexture::new(white, black, 1.0, CheckerSpace::Object);
+        assert_eq!(value(&object, 0.0, 0.0, Point3::new(0.5, 0.5, 0.5)), 1.0);
+        assert_eq!(value(&object, 0.0, 0.0, Point3::new(-0.5, 0.5, 0.5)), 0.0);
+        assert_eq!(value(&object, 0.0, 0.0, Point3::new(-0.5, -0.5, 0.5)), 1.0);
+    }
+}
@@ -0,0 +1,64 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    light::Spectrum,
+    utility::math::{ray::Ray3, float::Float, vector::{Point3, dot}}
+};
+use super::traits::{TextureLike, TextureCoordinates};
+
+// E==== IMPORTS }}}1
+
+/// What a gradient runs along.
+#[derive(Clone, Debug)]
+pub enum GradientDirection {
+    /// From $u = 0$ to $u = 1$.
+    U,
+    /// From $v = 0$ to $v = 1$.
+    V,
+    /// From `start` to `end`, in the local coordinates of the shape. The gradient is
+    /// constant on planes perpendicular to the line between them.
+    Object { start: Point3, end: Point3 },
+}
+
+/// Blends linearly from one texture to another, and stays at either end beyond it.
+#[derive(Debug)]
+pub struct GradientTexture {
+    start: Arc<dyn TextureLike>,
+    end: Arc<dyn TextureLike>,
+    direction: GradientDirection,
+}
+
+impl TextureLike for GradientTexture {
+    fn value_at(&self, incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        let t = match &self.direction {
+            GradientDirection::U => coordinate.u,
+            GradientDirection::V => coordinate.v,
+            GradientDirection::Object { start, end } => {
+                let axis = end - start;
+                let length_squared = dot(&axis, &axis);
+                if length_squared > 0.0 {
+                    dot(&(&coordinate.object_point - start), &axis) / length_squared
+                } else {
+                    0.0
+                }
+            },
+        };
+        let t = Float::clamp(t, 0.0, 1.0);
+
+        let start = self.start.value_at(incoming_ray, coordinate);
+        let end = self.end.value_at(incoming_ray, coordinate);
+        Arc::new((1.0 - t) * start.as_ref() + t * end.as_ref())
+    }
+}
+
+impl GradientTexture {
+    pub fn new(start: Arc<dyn TextureLike>, end: Arc<dyn TextureLike>, direction: GradientDirection) -> Self {
+        Self {
+            start,
+            end,
+            direction,
+        }
+    }
+}
@@ -0,0 +1,377 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    light::Spectrum,
+    utility::{
+        math::{ray::Ray3, float::Float, vector::Color3},
+        image::DecodedImage
+    }
+};
+use super::traits::{TextureLike, TextureCoordinates};
+
+// E==== IMPORTS }}}1
+
+/// How the texels around a lookup are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextureFilter {
+    /// The texel the lookup falls in.
+    Nearest,
+    /// The four texels whose centers surround the lookup, weighed by how close they are.
+    Bilinear,
+    /// Bilinear lookups in the two levels of the mipmap whose texels are about the size
+    /// of the area a pixel covers, blended by how close they are to that size.
+    Trilinear,
+    /// An elliptically weighted average (Heckbert's EWA) over the ellipse a pixel 
+    /// covers, which stays sharp where the texture is seen at grazing angles.
+    Ewa,
+}
+
+/// What lookups outside of $[0, 1]^2$ see.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WrapMode {
+    /// The image tiles the plane.
+    Repeat,
+    /// The texels along the edges are stretched outwards.
+    Clamp,
+    /// The image tiles the plane, with every other copy flipped.
+    Mirror,
+}
+
+impl WrapMode {
+    /// The texel that index `i` refers to, in a row or column of `size` texels.
+    fn wrap(&self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::Clamp => i.clamp(0, size - 1),
+            WrapMode::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size { i } else { 2 * size - 1 - i }
+            }
+        };
+
+        wrapped as usize
+    }
+}
+
+/// The EWA filter is cut off for ellipses more than this many times as long as they are
+/// wide, by widening them. Otherwise a single lookup could cover a whole row of texels.
+const MAX_ANISOTROPY: Float = 8.0;
+/// The falloff of the Gaussian that EWA weighs texels with.
+const EWA_ALPHA: Float = 2.0;
+
+/// One level of a mipmap: the image, downsampled by some power of 2.
+#[derive(Debug)]
+struct MipLevel {
+    width: usize,
+    height: usize,
+    /// Linear colors, row by row starting from the top of the image.
+    texels: Vec<Spectrum>,
+}
+
+impl MipLevel {
+    /// The level above this one, half as large each way (rounding up), each texel being 
+    /// the average of the (up to) 4 below it.
+    fn downsample(&self) -> Self {
+        let width = usize::max(1, self.width.div_ceil(2));
+        let height = usize::max(1, self.height.div_ceil(2));
+
+        let mut texels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let xs = [2 * x, usize::min(2 * x + 1, self.width - 1)];
+                let ys = [2 * y, usize::min(2 * y + 1, self.height - 1)];
+                let mut sum = Color3::new(0.0, 0.0, 0.0);
+                for y in ys {
+                    for x in xs {
+                        sum = sum + &self.texels[y * self.width + x];
+                    }
+                }
+                texels.push(0.25 * sum);
+            }
+        }
+
+        Self { width, height, texels }
+    }
+
+    /// The texel in column `x` and row `y` (counting from the top), wrapped into the image.
+    fn texel(&self, x: i64, y: i64, wrap_mode: WrapMode) -> &Spectrum {
+        let x = wrap_mode.wrap(x, self.width);
+        let y = wrap_mode.wrap(y, self.height);
+        &self.texels[y * self.width + x]
+    }
+
+    /// Continuous texel coordinates of $(u, v)$, in which texel centers are at half 
+    /// integers.
+    fn to_texel_space(&self, u: Float, v: Float) -> (Float, Float) {
+        (u * self.width as Float, (1.0 - v) * self.height as Float)
+    }
+
+    fn nearest(&self, u: Float, v: Float, wrap_mode: WrapMode) -> Spectrum {
+        let (x, y) = self.to_texel_space(u, v);
+        self.texel(x.floor() as i64, y.floor() as i64, wrap_mode).clone()
+    }
+
+    fn bilinear(&self, u: Float, v: Float, wrap_mode: WrapMode) -> Spectrum {
+        let (x, y) = self.to_texel_space(u, v);
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (dx, dy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        ((1.0 - dx) * (1.0 - dy)) * self.texel(x0, y0, wrap_mode)
+            + (dx * (1.0 - dy)) * self.texel(x0 + 1, y0, wrap_mode)
+            + ((1.0 - dx) * dy) * self.texel(x0, y0 + 1, wrap_mode)
+            + (dx * dy) * self.texel(x0 + 1, y0 + 1, wrap_mode)
+    }
+
+    /// The Gaussian weighted average of the texels in the ellipse centered at $(u, v)$
+    /// with (conjugate) semi-axes `axis0` and `axis1`, given in texture coordinates.
+    fn ewa(&self, u: Float, v: Float, axis0: (Float, Float), axis1: (Float, Float), wrap_mode: WrapMode) -> Spectrum {
+        let (x, y) = self.to_texel_space(u, v);
+        let (x, y) = (x - 0.5, y - 0.5);
+        // In texel space, where $v$ is flipped.
+        let (dx0, dy0) = (axis0.0 * self.width as Float, -axis0.1 * self.height as Float);
+        let (dx1, dy1) = (axis1.0 * self.width as Float, -axis1.1 * self.height as Float);
+
+        // The ellipse is $a x^2 + b x y + c y^2 < 1$, taken to be at least one texel 
+        // wide each way so that it never falls between texels.
+        let (a, b, c) = {
+            let a = dy0 * dy0 + dy1 * dy1 + 1.0;
+            let b = -2.0 * (dx0 * dy0 + dx1 * dy1);
+            let c = dx0 * dx0 + dx1 * dx1 + 1.0;
+            let inverse_f = 1.0 / (a * c - 0.25 * b * b);
+            (a * inverse_f, b * inverse_f, c * inverse_f)
+        };
+
+        // The bounding box of the ellipse.
+        let determinant = 4.0 * a * c - b * b;
+        let x_extent = 2.0 * Float::sqrt(c / determinant);
+        let y_extent = 2.0 * Float::sqrt(a / determinant);
+        let (x0, x1) = ((x - x_extent).ceil() as i64, (x + x_extent).floor() as i64);
+        let (y0, y1) = ((y - y_extent).ceil() as i64, (y + y_extent).floor() as i64);
+
+        let mut sum = Color3::new(0.0, 0.0, 0.0);
+        let mut total_weight = 0.0;
+        for ty in y0..=y1 {
+            for tx in x0..=x1 {
+                let (sx, sy) = (tx as Float - x, ty as Float - y);
+                let r_squared = a * sx * sx + b * sx * sy + c * sy * sy;
+                if r_squared < 1.0 {
+                    let weight = Float::exp(-EWA_ALPHA * r_squared) - Float::exp(-EWA_ALPHA);
+                    sum = sum + weight * self.texel(tx, ty, wrap_mode);
+                    total_weight += weight;
+                }
+            }
+        }
+
+        if total_weight > 0.0 {
+            (1.0 / total_weight) * sum
+        } else {
+            self.bilinear(u, v, wrap_mode)
+        }
+    }
+}
+
+/// A texture looked up in an image. The image covers $[0, 1]^2$ in texture coordinates,
+/// with $(0, 0)$ at its bottom left corner.
+///
+/// Unless it is sampled at full resolution, the image is kept as a mipmap: a pyramid of
+/// ever smaller copies of it, down to a single texel. Where a pixel covers many texels 
+/// (as given by the derivatives in `TextureCoordinates`), the lookup goes to a smaller 
+/// copy instead of picking out a few of them, which would alias.
+#[derive(Debug)]
+pub struct ImageTexture {
+    /// From the full image to a single texel.
+    levels: Vec<MipLevel>,
+    filter: TextureFilter,
+    wrap_mode: WrapMode,
+}
+
+impl TextureLike for ImageTexture {
+    fn value_at(&self, _incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        let (u, v) = (coordinate.u, coordinate.v);
+        let full_resolution = &self.levels[0];
+
+        let color = match self.filter {
+            TextureFilter::Nearest => full_resolution.nearest(u, v, self.wrap_mode),
+            TextureFilter::Bilinear => full_resolution.bilinear(u, v, self.wrap_mode),
+            TextureFilter::Trilinear => {
+                let width = Float::max(
+                    self.texel_length(coordinate.dudx, coordinate.dvdx),
+                    self.texel_length(coordinate.dudy, coordinate.dvdy)
+                );
+                self.trilinear(u, v, width)
+            },
+            TextureFilter::Ewa => self.ewa(u, v, coordinate),
+        };
+
+        Arc::new(color)
+    }
+}
+
+impl ImageTexture {
+    /// `texels` are linear colors, row by row starting from the top of the image.
+    pub fn new(
+        width: usize,
+        height: usize,
+        texels: Vec<Spectrum>,
+        filter: TextureFilter,
+        wrap_mode: WrapMode
+    ) -> Self {
+        assert_eq!(texels.len(), width * height, "wrong number of texels for the image size");
+
+        let mut levels = vec![MipLevel { width, height, texels }];
+        let needs_mipmap = matches!(filter, TextureFilter::Trilinear | TextureFilter::Ewa);
+        while needs_mipmap && (levels.last().unwrap().width > 1 || levels.last().unwrap().height > 1) {
+            let next = levels.last().unwrap().downsample();
+            levels.push(next);
+        }
+
+        Self {
+            levels,
+            filter,
+            wrap_mode,
+        }
+    }
+
+    /// The length, in texels of the full image, of the offset $(du, dv)$ in texture 
+    /// coordinates.
+    fn texel_length(&self, du: Float, dv: Float) -> Float {
+        let full_resolution = &self.levels[0];
+        let (dx, dy) = (du * full_resolution.width as Float, dv * full_resolution.height as Float);
+        Float::sqrt(dx * dx + dy * dy)
+    }
+
+    /// The (fractional) mipmap level whose texels are `width` texels of the full image 
+    /// across.
+    fn level_for_width(&self, width: Float) -> Float {
+        let max_level = (self.levels.len() - 1) as Float;
+        Float::clamp(Float::log2(Float::max(width, 1.0)), 0.0, max_level)
+    }
+
+    /// Looks up the levels on either side of the one whose texels are `width` texels of
+    /// the full image across, and blends them.
+    fn trilinear(&self, u: Float, v: Float, width: Float) -> Spectrum {
+        self.blend_levels(self.level_for_width(width), |level| level.bilinear(u, v, self.wrap_mode))
+    }
+
+    /// `lookup` in the levels on either side of `level`, blended.
+    fn blend_levels(&self, level: Float, lookup: impl Fn(&MipLevel) -> Spectrum) -> Spectrum {
+        let lower = level.floor() as usize;
+        if lower + 1 >= self.levels.len() {
+            return lookup(&self.levels[lower]);
+        }
+
+        let t = level - lower as Float;
+        if t == 0.0 {
+            return lookup(&self.levels[lower]);
+        }
+        (1.0 - t) * lookup(&self.levels[lower]) + t * lookup(&self.levels[lower + 1])
+    }
+
+    /// Follows pbrt: the level is picked by the minor axis of the ellipse, so that it
+    /// spans a few texels, and the ellipse is filtered over in that level.
+    fn ewa(&self, u: Float, v: Float, coordinate: &TextureCoordinates) -> Spectrum {
+        let mut major = (coordinate.dudx, coordinate.dvdx);
+        let mut minor = (coordinate.dudy, coordinate.dvdy);
+        let mut major_length = self.texel_length(major.0, major.1);
+        let mut minor_length = self.texel_length(minor.0, minor.1);
+        if minor_length > major_length {
+            std::mem::swap(&mut major, &mut minor);
+            std::mem::swap(&mut major_length, &mut minor_length);
+        }
+
+        if minor_length == 0.0 {
+            return self.trilinear(u, v, major_length);
+        }
+
+        if minor_length * MAX_ANISOTROPY < major_length {
+            let scale = major_length / (minor_length * MAX_ANISOTROPY);
+            minor = (scale * minor.0, scale * minor.1);
+            minor_length *= scale;
+        }
+
+        self.blend_levels(
+            self.level_for_width(minor_length), 
+            |level| level.ewa(u, v, major, minor, self.wrap_mode)
+        )
+    }
+
+    /// Reads an image file; see `DecodedImage::load()`.
+    pub fn load(
+        filename: &str,
+        filter: TextureFilter,
+        wrap_mode: WrapMode,
+        srgb: Option<bool>
+    ) -> Result<Self, String> {
+        let image = DecodedImage::load(filename, srgb)?;
+        Ok(Self::new(image.width, image.height, image.texels, filter, wrap_mode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::math::vector::Vec3;
+    use super::*;
+
+    fn lookup(texture: &ImageTexture, u: Float, v: Float) -> Spectrum {
+        let coordinates = TextureCoordinates::new(u, v, Vec3::new(0.0, 0.0, 1.0));
+        texture.value_at(&Ray3::default(), &coordinates).as_ref().clone()
+    }
+
+    #[test]
+    fn filtering_and_wrapping() {
+        // A 2 by 1 image, black on the left and white on the right.
+        let texels = vec![Color3::new(0.0, 0.0, 0.0), Color3::new(1.0, 1.0, 1.0)];
+        let nearest = ImageTexture::new(2, 1, texels.clone(), TextureFilter::Nearest, WrapMode::Repeat);
+        assert_eq!(lookup(&nearest, 0.2, 0.5).x(), 0.0);
+        assert_eq!(lookup(&nearest, 0.7, 0.5).x(), 1.0);
+        assert_eq!(lookup(&nearest, 1.2, 0.5).x(), 0.0);
+
+        // Halfway between the texel centers, and wrapping around the edge.
+        let bilinear = ImageTexture::new(2, 1, texels.clone(), TextureFilter::Bilinear, WrapMode::Repeat);
+        assert!((lookup(&bilinear, 0.5, 0.5).x() - 0.5).abs() < 1e-5);
+        assert!((lookup(&bilinear, 0.0, 0.5).x() - 0.5).abs() < 1e-5);
+        assert!((lookup(&bilinear, 0.375, 0.5).x() - 0.25).abs() < 1e-5);
+
+        let clamped = ImageTexture::new(2, 1, texels.clone(), TextureFilter::Bilinear, WrapMode::Clamp);
+        assert_eq!(lookup(&clamped, 0.0, 0.5).x(), 0.0);
+        assert_eq!(lookup(&clamped, 3.0, 0.5).x(), 1.0);
+
+        let mirrored = ImageTexture::new(2, 1, texels, TextureFilter::Nearest, WrapMode::Mirror);
+        assert_eq!(lookup(&mirrored, 1.2, 0.5).x(), 1.0);
+        assert_eq!(lookup(&mirrored, -0.2, 0.5).x(), 0.0);
+    }
+
+    #[test]
+    fn mipmaps_average_out_fine_detail() {
+        // A checkerboard of single texels, seen from far enough that a pixel covers 16
+        // by 16 of them, should look uniformly gray.
+        let size = 64;
+        let texels = (0..size * size)
+            .map(|i| {
+                let value = ((i % size + i / size) % 2) as Float;
+                Color3::new(value, value, value)
+            })
+            .collect::<Vec<_>>();
+
+        for filter in [TextureFilter::Trilinear, TextureFilter::Ewa] {
+            let texture = ImageTexture::new(size, size, texels.clone(), filter, WrapMode::Repeat);
+            let mut coordinates = TextureCoordinates::new(0.37, 0.61, Vec3::new(0.0, 0.0, 1.0));
+            coordinates.dudx = 0.25;
+            coordinates.dvdy = 0.25;
+            let value = texture.value_at(&Ray3::default(), &coordinates).x();
+            assert!((value - 0.5).abs() < 1e-3);
+
+            // Without derivatives, we get the texels themselves.
+            coordinates.dudx = 0.0;
+            coordinates.dvdy = 0.0;
+            coordinates.u = 0.5 / size as Float;
+            coordinates.v = 1.0 - 0.5 / size as Float;
+            assert!(texture.value_at(&Ray3::default(), &coordinates).x() < 0.1);
+        }
+    }
+}
@@ -2,3 +2,8 @@
 pub mod traits;
 pub mod constant;
 
+pub mod vertex_color;
+pub mod image_texture;
+pub mod checker;
+pub mod gradient;
+pub mod noise;
@@ -0,0 +1,91 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    light::Spectrum,
+    utility::math::{ray::Ray3, float::Float, noise::Perlin}
+};
+use super::traits::{TextureLike, TextureCoordinates};
+
+// E==== IMPORTS }}}1
+
+/// How the noise is turned into a blend between the two textures of a `NoiseTexture`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoisePattern {
+    /// A single layer of Perlin noise.
+    Perlin,
+    /// Several layers of noise at increasing frequencies; see `Perlin::fbm()`.
+    Fbm,
+    /// See `Perlin::turbulence()`.
+    Turbulence,
+    /// Bands along the $x$-axis, distorted by turbulence.
+    Marble,
+    /// Rings around the $y$-axis, distorted by turbulence.
+    Wood,
+}
+
+/// Blends between two textures according to noise in the local coordinates of the 
+/// shape, so that the pattern moves along with it. The noise varies over distances of
+/// about $1 / $`scale`.
+#[derive(Debug)]
+pub struct NoiseTexture {
+    /// Where the pattern is 0.
+    low: Arc<dyn TextureLike>,
+    /// Where the pattern is 1.
+    high: Arc<dyn TextureLike>,
+    pattern: NoisePattern,
+    scale: Float,
+    /// The number of layers of noise summed up by all but the `Perlin` pattern.
+    octaves: u32,
+    perlin: Perlin,
+}
+
+/// How much turbulence distorts the marble and wood patterns.
+const DISTORTION: Float = 4.0;
+
+impl TextureLike for NoiseTexture {
+    fn value_at(&self, incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        let point = self.scale * &coordinate.object_point;
+
+        let t = match self.pattern {
+            NoisePattern::Perlin => 0.5 * (1.0 + self.perlin.noise(&point)),
+            NoisePattern::Fbm => 0.5 * (1.0 + self.perlin.fbm(&point, self.octaves)),
+            NoisePattern::Turbulence => self.perlin.turbulence(&point, self.octaves),
+            NoisePattern::Marble => {
+                let phase = point.x() + DISTORTION * self.perlin.turbulence(&point, self.octaves);
+                0.5 * (1.0 + Float::sin(phase))
+            },
+            NoisePattern::Wood => {
+                let radius = Float::sqrt(point.x() * point.x() + point.z() * point.z());
+                let rings = radius + 0.25 * DISTORTION * self.perlin.turbulence(&point, self.octaves);
+                rings - rings.floor()
+            },
+        };
+        let t = Float::clamp(t, 0.0, 1.0);
+
+        let low = self.low.value_at(incoming_ray, coordinate);
+        let high = self.high.value_at(incoming_ray, coordinate);
+        Arc::new((1.0 - t) * low.as_ref() + t * high.as_ref())
+    }
+}
+
+impl NoiseTexture {
+    pub fn new(
+        low: Arc<dyn TextureLike>, 
+        high: Arc<dyn TextureLike>, 
+        pattern: NoisePattern, 
+        scale: Float, 
+        octaves: u32, 
+        seed: u32
+    ) -> Self {
+        Self {
+            low,
+            high,
+            pattern,
+            scale,
+            octaves,
+            perlin: Perlin::new(seed),
+        }
+    }
+}
@@ -1,27 +1,65 @@
 use std::{sync::Arc, fmt::Debug};
 use crate::{
-    utility::math::{float::Float, ray::Ray3, vector::Vec3}, 
+    utility::math::{float::Float, ray::Ray3, vector::{Vec3, Point3}}, 
     light::Spectrum
 };
 
+/// Where on a surface a texture is being looked up.
 #[derive(Debug)]
 pub struct TextureCoordinates {
-    u: Float,
-    v: Float,
-    normal: Vec3,
+    /// The surface parameters, each (for the built-in shapes) ranging over $[0, 1]$.
+    pub u: Float,
+    pub v: Float,
+    /// The shading normal at the point.
+    pub normal: Vec3,
+    /// The color interpolated from the vertices of meshes that have per-vertex colors.
+    pub vertex_color: Option<Spectrum>,
+    /// The point itself, in world space and in the local coordinates of the shape. 
+    /// Solid textures look these up, the latter so that they move along with the shape.
+    pub point: Point3,
+    pub object_point: Point3,
+    /// How $u$ and $v$ change from one pixel to the next, in $x$ and in $y$, which gives
+    /// the area of the texture a pixel covers. These are 0 when unknown, e.g. for points
+    /// not seen directly by the camera.
+    pub dudx: Float,
+    pub dudy: Float,
+    pub dvdx: Float,
+    pub dvdy: Float,
 }
 
 impl TextureCoordinates {
+    pub fn new(u: Float, v: Float, normal: Vec3) -> Self {
+        TextureCoordinates { 
+            u, 
+            v, 
+            normal, 
+            vertex_color: None, 
+            point: Point3::origin(), 
+            object_point: Point3::origin(), 
+            dudx: 0.0,
+            dudy: 0.0,
+            dvdx: 0.0,
+            dvdy: 0.0,
+        }
+    }
+
     pub fn default() -> Self {
         TextureCoordinates{
             u: 0.0,
             v: 0.0,
-            normal: Vec3::new(0.0,0.0,0.0)
+            normal: Vec3::new(0.0,0.0,0.0),
+            vertex_color: None,
+            point: Point3::origin(),
+            object_point: Point3::origin(),
+            dudx: 0.0,
+            dudy: 0.0,
+            dvdx: 0.0,
+            dvdy: 0.0,
         }
     }
 }
 
-pub trait TextureLike: Debug {
+pub trait TextureLike: Debug + Send + Sync {
     fn value_at(&self, incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum>;
 }
 
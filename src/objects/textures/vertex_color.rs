@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use crate::{light::Spectrum, utility::math::ray::Ray3};
+
+use super::traits::{TextureLike, TextureCoordinates};
+
+/// The color stored at the vertices of a mesh, interpolated across its triangles. Shapes
+/// without vertex colors get the fallback color instead.
+#[derive(Debug)]
+pub struct VertexColorTexture {
+    fallback: Arc<Spectrum>,
+}
+
+impl TextureLike for VertexColorTexture {
+    fn value_at(&self, _incoming_ray: &Ray3, coordinate: &TextureCoordinates) -> Arc<Spectrum> {
+        match &coordinate.vertex_color {
+            Some(color) => Arc::new(color.clone()),
+            None => self.fallback.clone(),
+        }
+    }
+}
+
+impl VertexColorTexture {
+    pub fn new(fallback: Spectrum) -> Self {
+        Self {
+            fallback: Arc::new(fallback),
+        }
+    }
+}
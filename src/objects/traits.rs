@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::utility::math::{vector::{Point3, Vec3}, float::Float, ray::Ray3};
 
@@ -7,7 +7,7 @@ use super::{textures::traits::TextureCoordinates, object::Object};
 
 pub struct ObjectIntersectionInfo {
     /// If there was no intersection, this value is `None`.
-    pub intersected_object: Option<Rc<Object>>,
+    pub intersected_object: Option<Arc<Object>>,
     pub point: Point3,
     pub t: Float,
     pub surface_normal: Vec3,
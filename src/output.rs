@@ -0,0 +1,130 @@
+//! What happens to the image once it is rendered.
+
+// S==== IMPORTS {{{1
+
+use std::{path::Path, time::Duration};
+use crate::utility::{
+    image::{Image, ExrPrecision},
+    post_process::{PostProcess, TransferFunction}
+};
+
+// E==== IMPORTS }}}1
+
+pub struct Output {
+    /// The file the image is saved to. Its extension determines the format (see 
+    /// `Image::save_to_file`).
+    path: String,
+    /// Only used when writing OpenEXR files.
+    exr_precision: ExrPrecision,
+    /// Applied to the image before it is saved. Its transfer function is replaced by
+    /// `transfer_function`, or, if that is `None`, by the one suiting the format.
+    post_process: PostProcess,
+    transfer_function: Option<TransferFunction>,
+    checkpoints: CheckpointSchedule,
+}
+
+/// When to save the image while it is still being rendered, overwriting the previous 
+/// checkpoint (the final image overwrites the last one). A checkpoint is due as soon as
+/// either interval has passed since the last one. With neither set, there are no 
+/// checkpoints.
+#[derive(Clone, Debug, Default)]
+pub struct CheckpointSchedule {
+    pub every_num_passes: Option<u32>,
+    pub every_duration: Option<Duration>,
+}
+
+impl CheckpointSchedule {
+    pub fn is_due(&self, passes_since_last: u32, time_since_last: Duration) -> bool {
+        let passes_due = self.every_num_passes
+            .is_some_and(|num_passes| passes_since_last >= num_passes);
+        let time_due = self.every_duration
+            .is_some_and(|duration| time_since_last >= duration);
+
+        passes_due || time_due
+    }
+}
+
+pub struct OutputInfo {
+    pub path: String,
+    pub exr_precision: ExrPrecision,
+    pub post_process: PostProcess,
+    pub transfer_function: Option<TransferFunction>,
+    pub checkpoints: CheckpointSchedule,
+}
+
+impl Output {
+    pub fn new(info: OutputInfo) -> Self {
+        Self {
+            path: info.path,
+            exr_precision: info.exr_precision,
+            post_process: info.post_process,
+            transfer_function: info.transfer_function,
+            checkpoints: info.checkpoints,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn set_path(&mut self, path: String) {
+        self.path = path;
+    }
+
+    pub fn checkpoints(&self) -> &CheckpointSchedule {
+        &self.checkpoints
+    }
+
+    pub fn write(&self, image: &Image) -> Result<(), String> {
+        let post_processed = self.post_process_for_path().apply(image);
+
+        if self.has_extension("exr") {
+            post_processed.save_to_exr(&self.path, self.exr_precision)
+        } else {
+            post_processed.save_to_file(&self.path)
+        }
+    }
+
+    /// 8-bit PNGs are encoded with the sRGB transfer function unless asked otherwise.
+    /// The other formats store floats, which are conventionally linear.
+    fn post_process_for_path(&self) -> PostProcess {
+        let transfer_function = self.transfer_function.unwrap_or(
+            if self.has_extension("png") { TransferFunction::Srgb } else { TransferFunction::Linear }
+        );
+
+        PostProcess {
+            transfer_function,
+            ..self.post_process.clone()
+        }
+    }
+
+    fn has_extension(&self, extension: &str) -> bool {
+        Path::new(&self.path)
+            .extension()
+            .is_some_and(|path_extension| path_extension.eq_ignore_ascii_case(extension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_are_due_after_either_interval() {
+        let passes_only = CheckpointSchedule { every_num_passes: Some(4), every_duration: None };
+        assert!(!passes_only.is_due(3, Duration::from_secs(1000)));
+        assert!(passes_only.is_due(4, Duration::ZERO));
+
+        let seconds_only = CheckpointSchedule { every_num_passes: None, every_duration: Some(Duration::from_secs(10)) };
+        assert!(!seconds_only.is_due(1000, Duration::from_secs(9)));
+        assert!(seconds_only.is_due(1, Duration::from_secs(10)));
+
+        let both = CheckpointSchedule { every_num_passes: Some(4), every_duration: Some(Duration::from_secs(10)) };
+        assert!(!both.is_due(3, Duration::from_secs(9)));
+        assert!(both.is_due(4, Duration::from_secs(1)));
+        assert!(both.is_due(1, Duration::from_secs(10)));
+
+        let neither = CheckpointSchedule::default();
+        assert!(!neither.is_due(1000, Duration::from_secs(1000)));
+    }
+}
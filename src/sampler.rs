@@ -21,6 +21,16 @@ pub fn cosine_on_2sphere_hemisphere(rng: &mut RandomNumberGenerator) -> SampleRe
     sphere_sampler_helper(rng, SphereSampleKind::CosineHemisphere)
 }
 
+pub fn uniform_on_2sphere(rng: &mut RandomNumberGenerator) -> SampleResult {
+    sphere_sampler_helper(rng, SphereSampleKind::UniformSphere)
+}
+
+/// Samples directions uniformly (wrt solid angle) within the cone around the $z$-axis 
+/// whose half-angle has cosine `cos_theta_max`.
+pub fn uniform_in_2sphere_cone(rng: &mut RandomNumberGenerator, cos_theta_max: Float) -> SampleResult {
+    sphere_sampler_helper(rng, SphereSampleKind::UniformCone { cos_theta_max })
+}
+
 pub fn uniform_in_1sphere(rng: &mut RandomNumberGenerator) -> SampleResult {
     let r = Float::sqrt(rng.next_float());
     let (sin_phi, cos_phi) = Float::sin_cos(2.0 * Float::get_pi() * rng.next_float());
@@ -31,6 +41,136 @@ pub fn uniform_in_1sphere(rng: &mut RandomNumberGenerator) -> SampleResult {
     }
 }
 
+/// Samples uniformly on the triangle with corners $(0,0)$, $(1,0)$ and $(0,1)$, i.e.
+/// the barycentric coordinates $(b_1, b_2)$ of the second and third corners of any
+/// triangle, which are returned in the $x$ and $y$ components.
+pub fn uniform_on_triangle(rng: &mut RandomNumberGenerator) -> SampleResult {
+    // How far we are from the corner (0,0), with the density growing like the length of
+    // the triangle's cross-section there, and then where along that cross-section.
+    let sqrt_u = Float::sqrt(rng.next_float());
+    let v = rng.next_float();
+
+    SampleResult {
+        point: Vec3::new(sqrt_u * (1.0 - v), sqrt_u * v, 0.0),
+        pdf: 2.0,
+    }
+}
+
+// S==== PIECEWISE CONSTANT DISTRIBUTIONS {{{1
+
+/// A density on $[0, 1)$ that is constant on each of $n$ equal pieces, proportional to
+/// the (nonnegative) values it was built from.
+pub struct PiecewiseConstant1D {
+    values: Vec<Float>,
+    /// $cdf_i$ is the probability of landing before piece $i$, with $cdf_n = 1$.
+    cdf: Vec<Float>,
+    /// The integral of the values over $[0, 1)$.
+    integral: Float,
+}
+
+impl PiecewiseConstant1D {
+    /// If all of `values` are zero, the density is uniform.
+    pub fn new(values: Vec<Float>) -> Self {
+        assert!(!values.is_empty(), "a piecewise constant distribution needs at least one piece");
+        let n = values.len() as Float;
+
+        let mut cdf = Vec::with_capacity(values.len() + 1);
+        cdf.push(0.0);
+        for (i, value) in values.iter().enumerate() {
+            cdf.push(cdf[i] + value / n);
+        }
+        let integral = cdf[values.len()];
+        for (i, c) in cdf.iter_mut().enumerate() {
+            *c = if integral > 0.0 { *c / integral } else { i as Float / n };
+        }
+
+        Self { values, cdf, integral }
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn integral(&self) -> Float {
+        self.integral
+    }
+
+    /// Samples a point in $[0, 1)$ (in the $x$ component) by inverting the CDF.
+    pub fn sample(&self, rng: &mut RandomNumberGenerator) -> SampleResult {
+        let u = rng.next_float();
+        let (x, pdf) = self.sample_with(u);
+        SampleResult { point: Vec3::new(x, 0.0, 0.0), pdf }
+    }
+
+    /// The point and its density that the uniform sample `u` is mapped to.
+    fn sample_with(&self, u: Float) -> (Float, Float) {
+        // The last piece whose CDF is at most `u`, skipping over empty pieces.
+        let piece = (self.cdf.partition_point(|&c| c <= u) - 1).min(self.len() - 1);
+        let width = self.cdf[piece + 1] - self.cdf[piece];
+        let offset = if width > 0.0 { (u - self.cdf[piece]) / width } else { 0.0 };
+        let x = ((piece as Float + offset) / self.len() as Float).min(1.0 - Float::EPSILON);
+
+        (x, self.pdf(x))
+    }
+
+    /// The density at `x` in $[0, 1)$.
+    pub fn pdf(&self, x: Float) -> Float {
+        let piece = self.piece(x);
+        if self.integral > 0.0 {
+            self.values[piece] / self.integral
+        } else {
+            1.0
+        }
+    }
+
+    fn piece(&self, x: Float) -> usize {
+        ((x * self.len() as Float) as usize).min(self.len() - 1)
+    }
+}
+
+/// A density on $[0, 1)^2$ that is constant on each cell of a grid, proportional to the
+/// values it was built from. It is sampled by picking a row by the marginal density of
+/// its values, and then a point in that row by the conditional density.
+pub struct PiecewiseConstant2D {
+    rows: Vec<PiecewiseConstant1D>,
+    marginal: PiecewiseConstant1D,
+}
+
+impl PiecewiseConstant2D {
+    /// `values` are given row by row, with `width` values in each.
+    pub fn new(values: &[Float], width: usize) -> Self {
+        assert!(width > 0 && values.len().is_multiple_of(width), "values don't fill a grid of the given width");
+
+        let rows: Vec<PiecewiseConstant1D> = values.chunks(width)
+            .map(|row| PiecewiseConstant1D::new(row.to_vec()))
+            .collect();
+        let marginal = PiecewiseConstant1D::new(rows.iter().map(|row| row.integral()).collect());
+
+        Self { rows, marginal }
+    }
+
+    /// Samples a point whose first coordinate (in the $x$ component) goes along the rows,
+    /// and whose second (in the $y$ component) picks the row.
+    pub fn sample(&self, rng: &mut RandomNumberGenerator) -> SampleResult {
+        let (y, _) = self.marginal.sample_with(rng.next_float());
+        let (x, _) = self.rows[self.marginal.piece(y)].sample_with(rng.next_float());
+
+        SampleResult { point: Vec3::new(x, y, 0.0), pdf: self.pdf(x, y) }
+    }
+
+    /// The density at $(x, y)$ in $[0, 1)^2$.
+    pub fn pdf(&self, x: Float, y: Float) -> Float {
+        let row = &self.rows[self.marginal.piece(y)];
+        if self.marginal.integral() > 0.0 {
+            row.values[row.piece(x)] / self.marginal.integral()
+        } else {
+            1.0
+        }
+    }
+}
+
+// E==== PIECEWISE CONSTANT DISTRIBUTIONS }}}1
+
 // S==== HELPERS {{{1
 
 enum SphereSampleKind {
@@ -38,6 +178,10 @@ enum SphereSampleKind {
     UniformHemisphere,
     // upper hemisphere, uniform wrt cosine / solid angle (higher distribution near top)
     CosineHemisphere,
+    /// the whole sphere, uniformly
+    UniformSphere,
+    /// the cap of the sphere around the $z$-axis above `cos_theta_max`, uniformly
+    UniformCone { cos_theta_max: Float },
 }
 
 /// Helper encapsulating various ways to sample on the unit sphere.
@@ -53,16 +197,25 @@ fn sphere_sampler_helper(rng: &mut RandomNumberGenerator, kind: SphereSampleKind
         SphereSampleKind::UniformHemisphere => {
             // By the Archimedes hat-box theorem, it suffices to sample the enscribing
             // cylinder.
-            pdf = 0.5 * Float::get_pi();
+            pdf = 0.5 * Float::get_1_pi();
             rng.next_float()
         },
         SphereSampleKind::CosineHemisphere => {
             let to_return = Float::sqrt(rng.next_float());
-            pdf = to_return * Float::get_pi();
+            pdf = to_return * Float::get_1_pi();
             to_return
-        }
+        },
+        SphereSampleKind::UniformSphere => {
+            pdf = 0.25 * Float::get_1_pi();
+            1.0 - 2.0 * rng.next_float()
+        },
+        SphereSampleKind::UniformCone { cos_theta_max } => {
+            // The same hat-box argument as for the hemisphere, restricted to the cap.
+            pdf = 1.0 / (2.0 * Float::get_pi() * (1.0 - cos_theta_max));
+            1.0 - rng.next_float() * (1.0 - cos_theta_max)
+        },
     };
-    let sin_theta = Float::sqrt(1.0 - cos_theta * cos_theta);
+    let sin_theta = Float::sqrt(Float::max(0.0, 1.0 - cos_theta * cos_theta));
     
     let sampled_vector = Vec3::new(
         cos_phi * sin_theta,
@@ -108,6 +261,30 @@ mod tests {
             write!(&mut file, "{},{},{}\n", point.x(), point.y(), point.z()).unwrap();
         }
     }
+
+    #[test]
+    fn piecewise_constant_2d_follows_the_values() {
+        // One empty cell, and one three times as likely as the other two.
+        let distribution = PiecewiseConstant2D::new(&[1.0, 0.0, 1.0, 3.0], 2);
+        let mut rng = RandomNumberGenerator::from_seed(1);
+
+        let mut counts = [0; 4];
+        let num_samples = 100_000;
+        for _ in 0..num_samples {
+            let sample = distribution.sample(&mut rng);
+            let (x, y) = (sample.point.x(), sample.point.y());
+            assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+            assert!((sample.pdf - distribution.pdf(x, y)).abs() < 1e-5);
+            counts[2 * (y >= 0.5) as usize + (x >= 0.5) as usize] += 1;
+        }
+
+        assert_eq!(counts[1], 0);
+        for (count, expected) in counts.iter().zip([0.2, 0.0, 0.2, 0.6]) {
+            assert!((*count as Float / num_samples as Float - expected).abs() < 0.01);
+        }
+        // Each cell has area 1/4, so the density there is 4 times its probability.
+        assert!((distribution.pdf(0.75, 0.75) - 2.4).abs() < 1e-5);
+    }
 }
 
 // E==== TESTS }}}1
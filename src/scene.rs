@@ -1,17 +1,26 @@
 //! This encapsulates all the geometry of the scene. 
 
 
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc, thread, time::Instant};
+use tracing::{info, warn};
 
-use crate::{camera::Camera, objects::{object_group::ObjectGroup, materials, textures, self}, integrators::{traits::IntegratorLike, ambient_occlusion::AmbientOcclusionIntegrator}, utility::{image::{Resolution, Image, ImageBuffer}, rng::RandomNumberGenerator, math::float::Float}};
+use crate::{camera::Camera, output::Output, objects::object_group::ObjectGroup, integrators::traits::{IntegratorLike, RenderContext}, lights::light_list::LightList, backgrounds::traits::BackgroundLike, utility::{image::{Image, ImageBuffer, Pixel, Tile}, rng::RandomNumberGenerator, interrupt, math::{float::Float, vector::Color3}}};
+
+/// The side length, in pixels, of the square tiles the image is divided into for 
+/// rendering.
+const TILE_SIZE: u32 = 16;
 
 pub struct Scene {
     integrator: Box<dyn IntegratorLike>,
     camera: Camera,
     objects: ObjectGroup, 
+    lights: LightList,
+    background: Arc<dyn BackgroundLike>,
     rng: RandomNumberGenerator,
     num_samples: u32,
     recursive_depth_limit: u32,
+    num_threads: usize,
+    output: Output,
 }
 
 impl Debug for Scene {
@@ -24,49 +33,151 @@ pub struct SceneInfo {
     pub integrator: Box<dyn IntegratorLike>,
     pub camera: Camera,
     pub objects: ObjectGroup, 
+    pub lights: LightList,
+    pub background: Arc<dyn BackgroundLike>,
     pub rng: RandomNumberGenerator,
     pub num_samples: u32,
     pub recursive_depth_limit: u32,
+    pub output: Output,
 }
 
 impl Scene {
+    /// The scene renders on as many threads as the machine can run in parallel, unless
+    /// told otherwise with `set_num_threads`.
     pub fn new(info: SceneInfo) -> Self {
+        let num_threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+
         Self {
             integrator: info.integrator,
             camera: info.camera,
             objects: info.objects,
+            lights: info.lights,
+            background: info.background,
             rng: info.rng,
             num_samples: info.num_samples,
             recursive_depth_limit: info.recursive_depth_limit,
+            num_threads,
+            output: info.output,
         }
     }
 
+    /// Renders are only reproducible when using the same number of threads, as each
+    /// thread draws from its own random number generator.
+    pub fn set_num_threads(&mut self, num_threads: usize) {
+        self.num_threads = usize::max(num_threads, 1);
+    }
+
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut Output {
+        &mut self.output
+    }
+
+    /// The image is divided into tiles, which are dealt out to the threads in turn (so
+    /// with $n$ threads, thread $i$ renders tiles $i$, $i + n$, $i + 2n$, ...). Each
+    /// thread gets a random number generator split off from the scene's, and keeps it for
+    /// every sample. Together, this makes the result depend only on the scene and the
+    /// number of threads, and not on how the threads happen to be scheduled.
+    ///
+    /// Between passes, we save checkpoints as scheduled by the output, and stop early if
+    /// the render was interrupted (see `utility::interrupt`).
     pub fn ray_trace(&mut self) -> Image {
-        let mut image_buffer = ImageBuffer::new(self.camera.get_resolution());
+        let resolution = self.camera.get_resolution();
+        let tiles = resolution.tiles(TILE_SIZE);
+        let mut rngs = self.rng.split(self.num_threads);
+        let mut image_buffer = ImageBuffer::new(resolution);
+
+        let mut last_checkpoint_time = Instant::now();
+        let mut last_checkpoint_samples = 0;
 
         while image_buffer.num_samples() < self.num_samples {
-            image_buffer.add_sample(self.ray_trace_single_sample());
+            image_buffer.add_sample(self.ray_trace_single_sample(&tiles, &mut rngs));
+            let num_samples = image_buffer.num_samples();
+
+            if interrupt::is_requested() {
+                warn!("interrupted after {} of {} samples", num_samples, self.num_samples);
+                break;
+            }
+
+            let checkpoint_is_due = self.output.checkpoints().is_due(
+                num_samples - last_checkpoint_samples, 
+                last_checkpoint_time.elapsed()
+            );
+            if checkpoint_is_due && num_samples < self.num_samples {
+                self.write_checkpoint(&image_buffer);
+                last_checkpoint_time = Instant::now();
+                last_checkpoint_samples = num_samples;
+            }
         }
         
         image_buffer.average_samples()
     }
 
-    fn ray_trace_single_sample(&mut self) -> Image {
-        let mut to_return = Image::new(self.camera.get_resolution());
+    /// Failing to save a checkpoint isn't worth stopping the render over.
+    fn write_checkpoint(&self, image_buffer: &ImageBuffer) {
+        match self.output.write(&image_buffer.average_samples()) {
+            Ok(_) => info!(
+                "saved checkpoint with {} of {} samples to '{}'", 
+                image_buffer.num_samples(), 
+                self.num_samples, 
+                self.output.path()
+            ),
+            Err(msg) => warn!("could not save checkpoint to '{}': {}", self.output.path(), msg),
+        }
+    }
+
+    /// Renders one sample of every pixel, using one thread for each generator in `rngs`.
+    fn ray_trace_single_sample(&self, tiles: &[Tile], rngs: &mut [RandomNumberGenerator]) -> Image {
+        let num_threads = rngs.len();
+
+        let rendered_pixels: Vec<Vec<(Pixel, Color3)>> = thread::scope(|scope| {
+            let handles: Vec<_> = rngs.iter_mut()
+                .enumerate()
+                .map(|(thread_index, rng)| scope.spawn(move || {
+                    let mut rendered: Vec<(Pixel, Color3)> = Vec::new();
+
+                    for tile in tiles.iter().skip(thread_index).step_by(num_threads) {
+                        for pixel in tile.pixels() {
+                            let pixel_color = self.ray_trace_pixel(&pixel, rng);
+                            rendered.push((pixel, pixel_color));
+                        }
+                    }
+
+                    rendered
+                }))
+                .collect();
+
+            handles.into_iter()
+                .map(|handle| handle.join().expect("a render thread panicked"))
+                .collect()
+        });
 
-        for pixel in self.camera.get_resolution().into_iter() {
-            let camera_ray = {
-                let px = (pixel.x as Float) + 0.5;
-                let py = (pixel.y as Float) + 0.5;
-                self.camera.generate_ray(px, py, &mut self.rng)
-            };
-            
-            let pixel_color = self.integrator.spectrum_from_ray(&self.objects, &camera_ray, &mut self.rng);
+        let mut to_return = Image::new(self.camera.get_resolution());
+        for (pixel, pixel_color) in rendered_pixels.into_iter().flatten() {
             to_return.set_pixel_color(&pixel, pixel_color);
         }
 
         to_return
     }
+
+    fn ray_trace_pixel(&self, pixel: &Pixel, rng: &mut RandomNumberGenerator) -> Color3 {
+        let camera_ray = {
+            let px = (pixel.x as Float) + 0.5;
+            let py = (pixel.y as Float) + 0.5;
+            self.camera.generate_ray(px, py, rng)
+        };
+        
+        let context = RenderContext {
+            objects: &self.objects,
+            lights: &self.lights,
+            background: self.background.as_ref(),
+        };
+        self.integrator.spectrum_from_ray(&context, &camera_ray, rng)
+    }
 }
 
 #[cfg(test)]
@@ -108,9 +219,9 @@ mod tests {
 //
 //     let ray = Ray3::new(Point3::origin(), Vec3::new(0.0,0.0,1.0));
 //
-//         let sphere_1 = Rc::new(Sphere::new(Point3::new(0.0,0.0,2.0), 1.0));
-//         let sphere_2 = Rc::new(Sphere::new(Point3::new(0.0,0.0,5.0), 1.0));
-//  let sphere_3 = Rc::new(Sphere::new(Point3::new(0.0,0.0,8.0), 1.0));
+//         let sphere_1 = Arc::new(Sphere::new(Point3::new(0.0,0.0,2.0), 1.0));
+//         let sphere_2 = Arc::new(Sphere::new(Point3::new(0.0,0.0,5.0), 1.0));
+//  let sphere_3 = Arc::new(Sphere::new(Point3::new(0.0,0.0,8.0), 1.0));
 //
 //         let scene = Scene {
 //             objects: vec![sphere_2, sphere_1, sphere_3],
@@ -0,0 +1,190 @@
+
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    backgrounds::{
+        traits::BackgroundLike,
+        constant::ConstantBackground,
+        gradient::GradientBackground,
+        environment_map::EnvironmentMap,
+        sky::{SkyBackground, MIN_TURBIDITY, MAX_TURBIDITY}
+    },
+    utility::math::{float::Float, vector::{Color3, Vec3}}
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const KIND_FIELD_NAME: &str = "kind";
+const CONSTANT_KIND: &str = "constant";
+const GRADIENT_KIND: &str = "gradient";
+const ENVIRONMENT_MAP_KIND: &str = "environment map";
+const SKY_KIND: &str = "sky";
+
+const RGB_FIELD_NAME: &str = "rgb color";
+
+const ZENITH_FIELD_NAME: &str = "zenith";
+const DEFAULT_ZENITH: [Float; 3] = [0.5, 0.7, 1.0];
+const HORIZON_FIELD_NAME: &str = "horizon";
+const DEFAULT_HORIZON: [Float; 3] = [1.0, 1.0, 1.0];
+const GROUND_FIELD_NAME: &str = "ground";
+
+const FILE_FIELD_NAME: &str = "file";
+const ROTATION_FIELD_NAME: &str = "rotation";
+const DEFAULT_ROTATION: Float = 0.0;
+const STRENGTH_FIELD_NAME: &str = "strength";
+const DEFAULT_STRENGTH: Float = 1.0;
+
+const SUN_DIRECTION_FIELD_NAME: &str = "sun direction";
+const TURBIDITY_FIELD_NAME: &str = "turbidity";
+const DEFAULT_TURBIDITY: Float = 3.0;
+const GROUND_ALBEDO_FIELD_NAME: &str = "ground albedo";
+const DEFAULT_GROUND_ALBEDO: [Float; 3] = [0.3, 0.3, 0.3];
+
+/// Scenes may give either a full description in `background_json`, or just a color in 
+/// `background_color_json`. Without either, the background is black.
+pub fn new_from_json(
+    background_json: &serde_json::Value, 
+    background_color_json: &serde_json::Value
+) -> Result<Arc<dyn BackgroundLike>, ParseError> {
+    if !background_json.is_null() {
+        return parse_background(background_json);
+    }
+
+    if background_color_json.is_null() {
+        return Ok(Arc::new(ConstantBackground::new(Color3::new(0.0, 0.0, 0.0))));
+    }
+    match serde_json::from_value::<Color3>(background_color_json.clone()) {
+        Ok(color) => Ok(Arc::new(ConstantBackground::new(color))),
+        Err(_) => {
+            let pe = ParseError {
+                msg: "could not parse the background color as an RGB color".to_string(),
+                json: background_color_json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn parse_background(json: &serde_json::Value) -> Result<Arc<dyn BackgroundLike>, ParseError> {
+    let kind_name = match &json[KIND_FIELD_NAME] {
+        serde_json::Value::String(s) => s.to_string(),
+        _ => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' for 'background'", KIND_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    match kind_name.as_str() {
+        CONSTANT_KIND => {
+            let color = get_color(json, RGB_FIELD_NAME, None)?;
+            Ok(Arc::new(ConstantBackground::new(color)))
+        },
+        GRADIENT_KIND => {
+            let zenith = get_color(json, ZENITH_FIELD_NAME, Some(DEFAULT_ZENITH))?;
+            let horizon = get_color(json, HORIZON_FIELD_NAME, Some(DEFAULT_HORIZON))?;
+            // The ground is the color of the horizon, unless told otherwise.
+            let ground = if json.get(GROUND_FIELD_NAME).is_none() {
+                horizon.clone()
+            } else {
+                get_color(json, GROUND_FIELD_NAME, None)?
+            };
+            Ok(Arc::new(GradientBackground::new(zenith, horizon, ground)))
+        },
+        ENVIRONMENT_MAP_KIND => Ok(Arc::new(parse_environment_map(json)?)),
+        SKY_KIND => Ok(Arc::new(parse_sky(json)?)),
+        other => {
+            let pe = ParseError {
+                msg: format!("unknown background kind '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+/// The RGB color in `field_name`, which is required unless there is a `default`.
+fn get_color(json: &serde_json::Value, field_name: &str, default: Option<[Float; 3]>) -> Result<Color3, ParseError> {
+    // Default value if none provided.
+    if let (None, Some([r, g, b])) = (json.get(field_name), default) {
+        return Ok(Color3::new(r, g, b));
+    }
+
+    match serde_json::from_value::<Color3>(json[field_name].clone()) {
+        Ok(c) => Ok(c),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' of the background as an RGB color", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+/// The number in `field_name`, or `default` if there is none.
+fn get_float(json: &serde_json::Value, field_name: &str, default: Float) -> Result<Float, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(default),
+        serde_json::Value::Number(n) if n.as_f64().is_some() => Ok(n.as_f64().unwrap() as Float),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' of the background must be a number", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn parse_environment_map(json: &serde_json::Value) -> Result<EnvironmentMap, ParseError> {
+    let filename = match &json[FILE_FIELD_NAME] {
+        serde_json::Value::String(s) => s.to_string(),
+        _ => {
+            let pe = ParseError {
+                msg: format!("environment map requires a file name in field '{}'", FILE_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+    let rotation = get_float(json, ROTATION_FIELD_NAME, DEFAULT_ROTATION)?;
+    let strength = get_float(json, STRENGTH_FIELD_NAME, DEFAULT_STRENGTH)?;
+
+    EnvironmentMap::load(&filename, Float::to_radians(rotation), strength)
+        .map_err(|msg| ParseError { msg, json: json.clone() })
+}
+
+fn parse_sky(json: &serde_json::Value) -> Result<SkyBackground, ParseError> {
+    let sun_direction = match serde_json::from_value::<Vec3>(json[SUN_DIRECTION_FIELD_NAME].clone()) {
+        Ok(direction) if direction.length() > 0.0 => direction,
+        _ => {
+            let pe = ParseError {
+                msg: format!("field '{}' of the sky must be a nonzero direction [x, y, z]", SUN_DIRECTION_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let turbidity = get_float(json, TURBIDITY_FIELD_NAME, DEFAULT_TURBIDITY)?;
+    if !(MIN_TURBIDITY..=MAX_TURBIDITY).contains(&turbidity) {
+        let pe = ParseError {
+            msg: format!(
+                "field '{}' of the sky must be between {} and {}, the range the sky model covers", 
+                TURBIDITY_FIELD_NAME, MIN_TURBIDITY, MAX_TURBIDITY
+            ),
+            json: json.clone(),
+        };
+        return Err(pe);
+    }
+
+    let ground_albedo = get_color(json, GROUND_ALBEDO_FIELD_NAME, Some(DEFAULT_GROUND_ALBEDO))?;
+    let strength = get_float(json, STRENGTH_FIELD_NAME, DEFAULT_STRENGTH)?;
+
+    Ok(SkyBackground::new(sun_direction, turbidity, ground_albedo, strength))
+}
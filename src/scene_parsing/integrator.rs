@@ -1,13 +1,18 @@
 
 // S==== IMPORTS {{{1
 
-use crate::integrators::{traits::IntegratorLike, ambient_occlusion::AmbientOcclusionIntegrator};
+use crate::integrators::{
+    traits::IntegratorLike, 
+    ambient_occlusion::AmbientOcclusionIntegrator, 
+    path::PathIntegrator
+};
 use super::parse_error::ParseError;
 
 // E==== IMPORTS }}}1
 
 const KIND_FIELD_NAME: &str = "kind";
 const AMBIENT_OCCLUSION_KIND: &str = "ambient occlusion";
+const PATH_KIND: &str = "path";
 
 const NUM_SAMPLES_FIELD_NAME: &str = "number of samples";
 const DEFAULT_NUM_SAMPLES: u32 = 64;
@@ -15,25 +20,33 @@ const DEFAULT_NUM_SAMPLES: u32 = 64;
 const RECURSION_LIMIT_FIELD_NAME: &str = "ray recursion limit";
 const DEFAULT_RECURSION_LIMIT: u32 = 64;
 
+const NEXT_EVENT_ESTIMATION_FIELD_NAME: &str = "next event estimation";
+const DEFAULT_NEXT_EVENT_ESTIMATION: bool = true;
+
 pub struct IntegratorParseOutput {
     pub integrator: Box<dyn IntegratorLike>,
     pub num_samples: u32,
     pub recursion_limit: u32,
+    /// Whether the integrator is a path tracer told not to sample lights, which then
+    /// can't find lights that aren't geometry.
+    pub skips_light_sampling: bool,
 }
 
 pub fn new_from_json(json: &serde_json::Value) -> Result<IntegratorParseOutput, ParseError> {
-    let integrator = get_integrator(json)?;
     let num_samples = get_num_samples(json)?;
     let recursion_limit = get_recursion_limit(json)?;
+    let (integrator, skips_light_sampling) = get_integrator(json, recursion_limit)?;
 
     Ok(IntegratorParseOutput {
         integrator,
         num_samples,
-        recursion_limit
+        recursion_limit,
+        skips_light_sampling,
     })
 }
 
-fn get_integrator(json: &serde_json::Value) -> Result<Box<dyn IntegratorLike>, ParseError> {
+/// The integrator, and whether it is a path tracer without next event estimation.
+fn get_integrator(json: &serde_json::Value, recursion_limit: u32) -> Result<(Box<dyn IntegratorLike>, bool), ParseError> {
     let integrator_name = match serde_json::from_value::<String>(json[KIND_FIELD_NAME].clone()) {
         Ok(s) => s,
         Err(_) => {
@@ -46,7 +59,11 @@ fn get_integrator(json: &serde_json::Value) -> Result<Box<dyn IntegratorLike>, P
     };
 
     match integrator_name.as_str() {
-        AMBIENT_OCCLUSION_KIND => Ok(Box::new(AmbientOcclusionIntegrator {})),
+        AMBIENT_OCCLUSION_KIND => Ok((Box::new(AmbientOcclusionIntegrator {}), false)),
+        PATH_KIND => {
+            let next_event_estimation = get_next_event_estimation(json)?;
+            Ok((Box::new(PathIntegrator::new(recursion_limit, next_event_estimation)), !next_event_estimation))
+        },
         other => {
             let pe = ParseError {
                 msg: format!("invalid integrator kind '{}'", other),
@@ -97,3 +114,20 @@ fn get_recursion_limit(json: &serde_json::Value) -> Result<u32, ParseError> {
     };
 }
 
+fn get_next_event_estimation(json: &serde_json::Value) -> Result<bool, ParseError> {
+    // Default value if none provided.
+    if json.get(NEXT_EVENT_ESTIMATION_FIELD_NAME).is_none() {
+        return Ok(DEFAULT_NEXT_EVENT_ESTIMATION);
+    }
+
+    match serde_json::from_value::<bool>(json[NEXT_EVENT_ESTIMATION_FIELD_NAME].clone()) {
+        Ok(b) => Ok(b),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' in 'integrator'", NEXT_EVENT_ESTIMATION_FIELD_NAME),
+                json: json.clone()
+            };
+            Err(pe)
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// S==== IMPORTS {{{1
+
+use std::sync::Arc;
+use crate::{
+    lights::{
+        traits::LightLike,
+        point::PointLight,
+        spot::SpotLight,
+        directional::DirectionalLight
+    },
+    utility::math::{float::Float, vector::Vec3}
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const KIND_FIELD_NAME: &str = "kind";
+const POINT_KIND: &str = "point";
+const SPOT_KIND: &str = "spot";
+const DIRECTIONAL_KIND: &str = "directional";
+
+const POSITION_FIELD_NAME: &str = "position";
+const DIRECTION_FIELD_NAME: &str = "direction";
+const INTENSITY_FIELD_NAME: &str = "intensity";
+const IRRADIANCE_FIELD_NAME: &str = "irradiance";
+const CONE_ANGLE_FIELD_NAME: &str = "cone angle";
+const DEFAULT_CONE_ANGLE: Float = 30.0;
+const FALLOFF_ANGLE_FIELD_NAME: &str = "falloff angle";
+const DEFAULT_FALLOFF_ANGLE: Float = 5.0;
+
+/// The "lights" section is optional. These are the lights that aren't objects in the 
+/// scene; emissive objects become lights on their own.
+pub fn parse_json(json: &serde_json::Value) -> Result<Vec<Arc<dyn LightLike>>, ParseError> {
+    let json_array: &Vec<serde_json::Value> = match json {
+        serde_json::Value::Array(arr) => arr,
+        serde_json::Value::Null => { return Ok(Vec::new()); },
+        _ => {
+            let pe = ParseError {
+                msg: "lights in json file are not listed as an array".to_string(),
+                json: json.clone()
+            };
+            return Err(pe);
+        }
+    };
+
+    json_array.iter().map(parse_single_light).collect()
+}
+
+fn parse_single_light(json: &serde_json::Value) -> Result<Arc<dyn LightLike>, ParseError> {
+    let kind_name = match &json[KIND_FIELD_NAME] {
+        serde_json::Value::String(s) => s.to_string(),
+        _ => {
+            let pe = ParseError {
+                msg: format!("light requires a string in field '{}'", KIND_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    match kind_name.as_str() {
+        POINT_KIND => {
+            let position = get_vector(json, POSITION_FIELD_NAME)?;
+            let intensity = get_vector(json, INTENSITY_FIELD_NAME)?;
+            Ok(Arc::new(PointLight::new(position, intensity)))
+        },
+        SPOT_KIND => {
+            let position = get_vector(json, POSITION_FIELD_NAME)?;
+            let direction = get_direction(json)?;
+            let intensity = get_vector(json, INTENSITY_FIELD_NAME)?;
+            let cone_angle = get_angle(json, CONE_ANGLE_FIELD_NAME, DEFAULT_CONE_ANGLE)?;
+            let falloff_angle = get_angle(json, FALLOFF_ANGLE_FIELD_NAME, DEFAULT_FALLOFF_ANGLE)?;
+            Ok(Arc::new(SpotLight::new(position, direction, intensity, cone_angle, falloff_angle)))
+        },
+        DIRECTIONAL_KIND => {
+            let direction = get_direction(json)?;
+            let irradiance = get_vector(json, IRRADIANCE_FIELD_NAME)?;
+            Ok(Arc::new(DirectionalLight::new(direction, irradiance)))
+        },
+        other => {
+            let pe = ParseError {
+                msg: format!("unknown light kind '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+/// The required triple of numbers (a point, vector or RGB color) in `field_name`.
+fn get_vector(json: &serde_json::Value, field_name: &str) -> Result<Vec3, ParseError> {
+    match serde_json::from_value::<Vec3>(json[field_name].clone()) {
+        Ok(v) => Ok(v),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("light requires three numbers in field '{}'", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_direction(json: &serde_json::Value) -> Result<Vec3, ParseError> {
+    let direction = get_vector(json, DIRECTION_FIELD_NAME)?;
+    if direction.length() == 0.0 {
+        let pe = ParseError {
+            msg: format!("field '{}' of the light can't be the zero vector", DIRECTION_FIELD_NAME),
+            json: json.clone(),
+        };
+        return Err(pe);
+    }
+
+    Ok(direction)
+}
+
+/// The angle in degrees between 0 and 180 in `field_name` (or `default`), in radians.
+fn get_angle(json: &serde_json::Value, field_name: &str, default: Float) -> Result<Float, ParseError> {
+    // Default value if none provided.
+    if json.get(field_name).is_none() {
+        return Ok(default.to_radians());
+    }
+
+    match serde_json::from_value::<Float>(json[field_name].clone()) {
+        Ok(degrees) if (0.0..=180.0).contains(&degrees) => Ok(degrees.to_radians()),
+        _ => {
+            let pe = ParseError {
+                msg: format!("field '{}' must be an angle between 0 and 180 degrees", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
@@ -1,22 +1,68 @@
 
 // S==== IMPORTS {{{1
 
-use std::{rc::Rc, collections::HashMap};
-use crate::objects::materials::{lambertian::Lambertian, traits::MaterialLike};
-use super::parse_error::ParseError;
+use std::{sync::Arc, collections::HashMap};
+use crate::{
+    objects::{
+        materials::{
+            lambertian::Lambertian, diffuse_light::DiffuseLight, metal::Metal, dielectric::Dielectric, 
+            conductor::Conductor, principled::{Principled, PrincipledParameters}, traits::MaterialLike
+        },
+    },
+    utility::math::{float::Float, vector::Color3}
+};
+use super::{parse_error::ParseError, textures::{TextureMap, get_texture_parameter}};
 
 // E==== IMPORTS }}}1
 
 const NAME_FIELD_NAME: &str = "name";
 const KIND_FIELD_NAME: &str = "kind";
 const LAMBERTIAN_KIND: &str = "lambertian";
+const DIFFUSE_LIGHT_KIND: &str = "diffuse light";
+const METAL_KIND: &str = "metal";
+const DIELECTRIC_KIND: &str = "dielectric";
+const CONDUCTOR_KIND: &str = "conductor";
+const PRINCIPLED_KIND: &str = "principled";
+
+const RADIANCE_FIELD_NAME: &str = "radiance";
+
+const TINT_FIELD_NAME: &str = "tint";
+const DEFAULT_TINT: [Float; 3] = [1.0, 1.0, 1.0];
+const FUZZ_FIELD_NAME: &str = "fuzz";
+const DEFAULT_FUZZ: Float = 0.0;
+const INDEX_OF_REFRACTION_FIELD_NAME: &str = "index of refraction";
+const DEFAULT_INDEX_OF_REFRACTION: Float = 1.5;
+const ROUGHNESS_FIELD_NAME: &str = "roughness";
+const DEFAULT_ROUGHNESS: Float = 0.0;
+const REFLECTANCE_FIELD_NAME: &str = "reflectance";
+const DEFAULT_REFLECTANCE: [Float; 3] = [0.9, 0.9, 0.9];
+
+// Each parameter of a principled material is a number, an RGB color, or the name of a 
+// texture.
+const BASE_COLOR_FIELD_NAME: &str = "base color";
+const DEFAULT_BASE_COLOR: Float = 0.8;
+const METALLIC_FIELD_NAME: &str = "metallic";
+const DEFAULT_METALLIC: Float = 0.0;
+const DEFAULT_PRINCIPLED_ROUGHNESS: Float = 0.5;
+const SPECULAR_FIELD_NAME: &str = "specular";
+const DEFAULT_SPECULAR: Float = 0.5;
+const SPECULAR_TINT_FIELD_NAME: &str = "specular tint";
+const DEFAULT_SPECULAR_TINT: Float = 0.0;
+const CLEARCOAT_FIELD_NAME: &str = "clearcoat";
+const DEFAULT_CLEARCOAT: Float = 0.0;
+const CLEARCOAT_ROUGHNESS_FIELD_NAME: &str = "clearcoat roughness";
+const DEFAULT_CLEARCOAT_ROUGHNESS: Float = 0.1;
+const SHEEN_FIELD_NAME: &str = "sheen";
+const DEFAULT_SHEEN: Float = 0.0;
+const SHEEN_TINT_FIELD_NAME: &str = "sheen tint";
+const DEFAULT_SHEEN_TINT: Float = 0.5;
 
 pub struct MaterialMap {
-    map: HashMap<String, Rc<dyn MaterialLike>>
+    map: HashMap<String, Arc<dyn MaterialLike>>
 }
 
 impl MaterialMap {
-    pub fn get(&self, key: &str) -> Result<Rc<dyn MaterialLike>, ParseError> {
+    pub fn get(&self, key: &str) -> Result<Arc<dyn MaterialLike>, ParseError> {
         match self.map.get(key) {
             Some(val) => Ok(val.clone()),
             None => {
@@ -30,7 +76,8 @@ impl MaterialMap {
     }
 }
 
-pub fn parse_json(json: &serde_json::Value) -> Result<MaterialMap, ParseError> {
+/// Materials may refer to textures by name, so `textures` should already be parsed.
+pub fn parse_json(json: &serde_json::Value, textures: &TextureMap) -> Result<MaterialMap, ParseError> {
     let json_array: &Vec<serde_json::Value> = match json {
         serde_json::Value::Array(arr) => arr,
         _ => {
@@ -42,9 +89,9 @@ pub fn parse_json(json: &serde_json::Value) -> Result<MaterialMap, ParseError> {
         }
     };   
 
-    let mut to_return: HashMap<String, Rc<dyn MaterialLike>> = HashMap::new();
+    let mut to_return: HashMap<String, Arc<dyn MaterialLike>> = HashMap::new();
     for material in json_array.iter() {
-        let result = parse_single_material(&material)?;
+        let result = parse_single_material(material, textures)?;
         to_return.insert(result.0, result.1);
     }
 
@@ -53,14 +100,34 @@ pub fn parse_json(json: &serde_json::Value) -> Result<MaterialMap, ParseError> {
     })
 }
 
-fn parse_single_material(json: &serde_json::Value) -> Result<(String, Rc<dyn MaterialLike>), ParseError> {
+fn parse_single_material(json: &serde_json::Value, textures: &TextureMap) -> Result<(String, Arc<dyn MaterialLike>), ParseError> {
     let name = get_name(json)?;
 
     let kind_name = get_kind_name(json)?; 
     match kind_name.as_str() {
         LAMBERTIAN_KIND => {
             let material = Lambertian {}; 
-            return Ok((name, Rc::new(material)));
+            return Ok((name, Arc::new(material)));
+        },
+        DIFFUSE_LIGHT_KIND => {
+            let material = parse_diffuse_light(json, textures)?;
+            return Ok((name, Arc::new(material)));
+        },
+        METAL_KIND => {
+            let material = parse_metal(json)?;
+            Ok((name, Arc::new(material)))
+        },
+        DIELECTRIC_KIND => {
+            let material = parse_dielectric(json)?;
+            Ok((name, Arc::new(material)))
+        },
+        CONDUCTOR_KIND => {
+            let material = parse_conductor(json)?;
+            Ok((name, Arc::new(material)))
+        },
+        PRINCIPLED_KIND => {
+            let material = parse_principled(json, textures)?;
+            Ok((name, Arc::new(material)))
         },
         other => {
             let pe = ParseError {
@@ -112,6 +179,115 @@ fn get_kind_name(json: &serde_json::Value) -> Result<String, ParseError> {
     };
 }
 
+fn parse_diffuse_light(json: &serde_json::Value, textures: &TextureMap) -> Result<DiffuseLight, ParseError> {
+    let radiance = match json[RADIANCE_FIELD_NAME].as_str() {
+        Some(texture_name) => textures.get(texture_name)?,
+        None => {
+            let pe = ParseError {
+                msg: format!("diffuse light requires the name of a texture in field '{}'", RADIANCE_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    Ok(DiffuseLight::new(radiance))
+}
+
+/// The RGB color in `field_name`, or `default` if there is none.
+fn get_color(json: &serde_json::Value, field_name: &str, default: [Float; 3]) -> Result<Color3, ParseError> {
+    // Default value if none provided.
+    if json.get(field_name).is_none() {
+        let [r, g, b] = default;
+        return Ok(Color3::new(r, g, b));
+    }
+
+    match serde_json::from_value::<Color3>(json[field_name].clone()) {
+        Ok(c) => Ok(c),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' as an RGB color", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+/// The number in $[0, 1]$ in `field_name`, or `default` if there is none.
+fn get_unit_interval(json: &serde_json::Value, field_name: &str, default: Float) -> Result<Float, ParseError> {
+    // Default value if none provided.
+    if json.get(field_name).is_none() {
+        return Ok(default);
+    }
+
+    match serde_json::from_value::<Float>(json[field_name].clone()) {
+        Ok(f) if (0.0..=1.0).contains(&f) => Ok(f),
+        _ => {
+            let pe = ParseError {
+                msg: format!("field '{}' must be a number between 0 and 1", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn parse_metal(json: &serde_json::Value) -> Result<Metal, ParseError> {
+    let tint = get_color(json, TINT_FIELD_NAME, DEFAULT_TINT)?;
+    let fuzz = get_unit_interval(json, FUZZ_FIELD_NAME, DEFAULT_FUZZ)?;
+
+    Ok(Metal::new(tint, fuzz))
+}
+
+fn parse_conductor(json: &serde_json::Value) -> Result<Conductor, ParseError> {
+    let reflectance = get_color(json, REFLECTANCE_FIELD_NAME, DEFAULT_REFLECTANCE)?;
+    let roughness = get_unit_interval(json, ROUGHNESS_FIELD_NAME, DEFAULT_ROUGHNESS)?;
+
+    Ok(Conductor::new(reflectance, roughness))
+}
+
+fn parse_dielectric(json: &serde_json::Value) -> Result<Dielectric, ParseError> {
+    let tint = get_color(json, TINT_FIELD_NAME, DEFAULT_TINT)?;
+    let roughness = get_unit_interval(json, ROUGHNESS_FIELD_NAME, DEFAULT_ROUGHNESS)?;
+
+    // Default value if none provided.
+    let index_of_refraction = if json.get(INDEX_OF_REFRACTION_FIELD_NAME).is_none() {
+        DEFAULT_INDEX_OF_REFRACTION
+    } else {
+        match serde_json::from_value::<Float>(json[INDEX_OF_REFRACTION_FIELD_NAME].clone()) {
+            Ok(ior) if ior > 0.0 => ior,
+            _ => {
+                let pe = ParseError {
+                    msg: format!("field '{}' must be a positive number", INDEX_OF_REFRACTION_FIELD_NAME),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        }
+    };
+
+    Ok(Dielectric::new(index_of_refraction, roughness, tint))
+}
+
+fn parse_principled(json: &serde_json::Value, textures: &TextureMap) -> Result<Principled, ParseError> {
+    let parameters = PrincipledParameters {
+        base_color: get_texture_parameter(json, BASE_COLOR_FIELD_NAME, DEFAULT_BASE_COLOR, textures)?,
+        metallic: get_texture_parameter(json, METALLIC_FIELD_NAME, DEFAULT_METALLIC, textures)?,
+        roughness: get_texture_parameter(json, ROUGHNESS_FIELD_NAME, DEFAULT_PRINCIPLED_ROUGHNESS, textures)?,
+        specular: get_texture_parameter(json, SPECULAR_FIELD_NAME, DEFAULT_SPECULAR, textures)?,
+        specular_tint: get_texture_parameter(json, SPECULAR_TINT_FIELD_NAME, DEFAULT_SPECULAR_TINT, textures)?,
+        clearcoat: get_texture_parameter(json, CLEARCOAT_FIELD_NAME, DEFAULT_CLEARCOAT, textures)?,
+        clearcoat_roughness: get_texture_parameter(
+            json, CLEARCOAT_ROUGHNESS_FIELD_NAME, DEFAULT_CLEARCOAT_ROUGHNESS, textures
+        )?,
+        sheen: get_texture_parameter(json, SHEEN_FIELD_NAME, DEFAULT_SHEEN, textures)?,
+        sheen_tint: get_texture_parameter(json, SHEEN_TINT_FIELD_NAME, DEFAULT_SHEEN_TINT, textures)?,
+    };
+
+    Ok(Principled::new(parameters))
+}
+
 // S==== TESTS {{{1
 
 #[cfg(test)]
@@ -7,8 +7,9 @@
 //! {
 //!     "camera": ...,
 //!     "integrator": ...,
-//!     "background color": ...,
-//!
+//!     "textures": ...,
+//!     "materials": ...,
+//!     "objects": ...
 //! }
 //! ```
 //!
@@ -16,7 +17,62 @@
 //! ```
 //! {
 //!     ...,
-//!     "background color": []
+//!     "background": ...,
+//!     "background color": [0, 0, 0],
+//!     "lights": [],
+//!     "output": ...
+//! }
+//! ```
+//!
+//! ## background
+//!
+//! What rays that escape the scene see, which also lights the scene. "background 
+//! color" is a shorthand for a constant background, and is ignored if "background" is 
+//! given. Directions are in world space, with +y up.
+//!
+//! ```
+//! "background": { "kind": "constant", "rgb color": [r, g, b] }
+//! ```
+//!
+//! A sky blending from "horizon" to "zenith" straight up, and to "ground" straight down:
+//! ```
+//! "background": {
+//!     "kind": "gradient",
+//!     "zenith": [r, g, b],    (optional, default [0.5, 0.7, 1])
+//!     "horizon": [r, g, b],   (optional, default [1, 1, 1])
+//!     "ground": [r, g, b]     (optional, default the horizon color)
+//! }
+//! ```
+//!
+//! An image (usually HDR) in the latitude-longitude layout, whose path is relative to 
+//! the working directory. Its left edge looks along +x, a quarter of the way across 
+//! along +z, and the top straight up. "rotation" turns it about the y-axis from +x 
+//! towards +z, in degrees, and "strength" multiplies it. With next event estimation, 
+//! the path integrator samples directions towards it in proportion to its brightness,
+//! like it does the emissive objects.
+//! ```
+//! "background": {
+//!     "kind": "environment map",
+//!     "file": String,
+//!     "rotation": Float,   (optional, default 0)
+//!     "strength": Float    (optional, default 1)
+//! }
+//! ```
+//!
+//! A daylight sky (Preetham et al.'s model) with the sun, above a diffuse ground lit by
+//! both. "sun direction" points towards the sun, and need not be normalized. Once the
+//! sun has set, the sky fades to black as it sinks about 6 degrees below the horizon.
+//! "turbidity" is how hazy the air is, from 2 (clear) to 10; values outside 1.7 to 10,
+//! the range the model covers, are rejected. "strength" multiplies the light of the sky
+//! and sun, which is scaled so that a clear midday sky is around 1. The sun is sampled
+//! like the environment map.
+//! ```
+//! "background": {
+//!     "kind": "sky",
+//!     "sun direction": [x, y, z],
+//!     "turbidity": Float,            (optional, default 3)
+//!     "ground albedo": [r, g, b],    (optional, default [0.3, 0.3, 0.3])
+//!     "strength": Float              (optional, default 1)
 //! }
 //! ```
 //!
@@ -40,6 +96,52 @@
 //! }
 //! ```
 //!
+//! ### path
+//!
+//! A full path tracer. Paths are terminated once they have bounced "ray recursion 
+//! limit" many times. With "next event estimation" on, lights (objects with an 
+//! emissive material, and those in "lights") are also sampled directly at each bounce.
+//! Without it, the point, spot and directional lights give no light at all, so scenes
+//! with any are rejected.
+//!
+//! ```
+//! {
+//!     "kind": "path",
+//!     "next event estimation": Boolean (default true),
+//!     ...
+//! }
+//! ```
+//!
+//! ## output
+//!
+//! Optional, as are its fields. The format of the image is deduced from the extension
+//! of "path": ".png" (8-bit sRGB), ".hdr" (floating point Radiance RGBE), or, to keep
+//! the rendered values exactly, ".exr" (OpenEXR) or ".pfm" (portable float map). A path 
+//! given on the command line takes precedence.
+//!
+//! Before it is saved, the image can be post-processed: it is scaled by 
+//! 2^"exposure", tone mapped, encoded with a transfer function, and clamped to [0, 1], 
+//! in that order. The transfer function defaults to "srgb" for ".png" files and to 
+//! "linear" otherwise.
+//!
+//! ```
+//! "output": {
+//!     "path": String (default "render.png"),
+//!     "exr precision": "half" | "float" (default "float"),
+//!     "exposure": Float (default 0),
+//!     "tone mapping": "none" | "reinhard" | "aces filmic" (default "none"),
+//!     "transfer function": "linear" | "srgb",
+//!     "clamp": Boolean (default false),
+//!     "checkpoint passes": Unsigned Integer (at least 1),
+//!     "checkpoint seconds": Float (positive)
+//! }
+//! ```
+//!
+//! Setting either of the last two renders progressively: the image (averaged over the 
+//! samples so far) is also saved every that many passes (samples per pixel) or seconds,
+//! whichever comes first. In any case, pressing Ctrl-C stops the render after the 
+//! current pass and saves what there is.
+//!
 //! ## camera
 //!
 //! ```
@@ -81,6 +183,124 @@
 //!
 //! #### lambertian
 //!
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "lambertian"
+//! }
+//! ```
+//!
+//! #### diffuse light
+//!
+//! Emits the color of the texture `"radiance"` from the side of the surface its
+//! normal points towards, and does not scatter light.
+//!
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "diffuse light",
+//!     "radiance": Name of Texture
+//! }
+//! ```
+//!
+//! #### metal
+//!
+//! Reflects like a mirror, with the reflections colored by "tint" (and the object's 
+//! texture). A "fuzz" between 0 and 1 blurs the reflections, for rougher metals.
+//!
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "metal",
+//!     "tint": [r, g, b],   (optional, default [1, 1, 1])
+//!     "fuzz": Float        (optional, default 0)
+//! }
+//! ```
+//!
+//! #### dielectric
+//!
+//! A transparent material like glass, which both reflects and refracts. The "index of 
+//! refraction" is that of the inside of the shape, with the outside taken to be vacuum.
+//! A "roughness" between 0 (smooth) and 1 makes it frosted.
+//!
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "dielectric",
+//!     "index of refraction": Float,   (optional, default 1.5)
+//!     "roughness": Float,             (optional, default 0)
+//!     "tint": [r, g, b]               (optional, default [1, 1, 1])
+//! }
+//! ```
+//!
+//! #### conductor
+//!
+//! A metal made up of microscopic mirrors, whose orientations spread out more the 
+//! higher the "roughness" (between 0 and 1) is. The "reflectance" is the color of the 
+//! reflections seen head on, and they turn white towards grazing angles.
+//!
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "conductor",
+//!     "reflectance": [r, g, b],   (optional, default [0.9, 0.9, 0.9])
+//!     "roughness": Float          (optional, default 0)
+//! }
+//! ```
+//!
+//! #### principled
+//!
+//! Disney's principled material, with the parameters DCC tools export. Each parameter 
+//! may be a number, an RGB color, or the name of a texture; all but "base color" are 
+//! between 0 and 1. Since the object's texture still multiplies the result, it should 
+//! normally be white.
+//!
+//! ```
+//! {
+//!     "name": Name,
+//!     "kind": "principled",
+//!     "base color": Parameter,            (optional, default 0.8)
+//!     "metallic": Parameter,              (optional, default 0)
+//!     "roughness": Parameter,             (optional, default 0.5)
+//!     "specular": Parameter,              (optional, default 0.5)
+//!     "specular tint": Parameter,         (optional, default 0)
+//!     "clearcoat": Parameter,             (optional, default 0)
+//!     "clearcoat roughness": Parameter,   (optional, default 0.1)
+//!     "sheen": Parameter,                 (optional, default 0)
+//!     "sheen tint": Parameter             (optional, default 0.5)
+//! }
+//! ```
+//!
+//! ## lights
+//!
+//! Lights that aren't geometry, and so can't be seen directly, listed as an array. 
+//! Intensities are in power per unit solid angle, and fall off with the square of the 
+//! distance.
+//!
+//! ```
+//! { "kind": "point", "position": [x, y, z], "intensity": [r, g, b] }
+//! ```
+//!
+//! A point light shining within the cone around "direction" with half-angle "cone
+//! angle". It fades out smoothly over the outermost "falloff angle" of the cone. Angles
+//! are in degrees.
+//! ```
+//! {
+//!     "kind": "spot",
+//!     "position": [x, y, z],
+//!     "direction": [x, y, z],
+//!     "intensity": [r, g, b],
+//!     "cone angle": Float,      (optional, default 30)
+//!     "falloff angle": Float    (optional, default 5)
+//! }
+//! ```
+//!
+//! Light from infinitely far away travelling along "direction", with "irradiance" the
+//! power per unit area arriving at a surface facing it.
+//! ```
+//! { "kind": "directional", "direction": [x, y, z], "irradiance": [r, g, b] }
+//! ```
+//!
 //! ## textures
 //!
 //! The basic setup is an array as follows:
@@ -107,6 +327,86 @@
 //!     "rgb_color": [r,g,b]
 //! }
 //! ```
+//!
+//! ### Vertex color texture
+//! The colors of the vertices of a mesh (see the ply shape), interpolated across its
+//! triangles. Shapes without vertex colors get the fallback color, which defaults to 
+//! white.
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "vertex color",
+//!     "fallback rgb color": [r,g,b]   (optional)
+//! }
+//! ```
+//!
+//! ### Image texture
+//! An image file (PNG, JPEG, HDR, ...), whose path is relative to the working directory,
+//! stretched over texture coordinates $[0, 1]^2$ with $(0, 0)$ at its bottom left. The 
+//! "filter" is "nearest", "bilinear", or one of "trilinear" and "ewa", which look up a
+//! mipmap according to how much of the texture a pixel covers, to avoid aliasing. The 
+//! "wrap" mode, which decides what lies outside of $[0, 1]^2$, is "repeat", "clamp" or
+//! "mirror". Values are decoded from sRGB unless "srgb" is false, which is the default 
+//! for floating point images like HDR.
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "image",
+//!     "file": String,
+//!     "filter": String,   (optional, default "trilinear")
+//!     "wrap": String,     (optional, default "repeat")
+//!     "srgb": Bool        (optional)
+//! }
+//! ```
+//!
+//! ### Procedural textures
+//! These combine other textures, given (like the parameters of the principled material)
+//! as a number, an RGB color, or the name of a texture listed earlier in the array. 
+//! "object" space means the local coordinates of the shape, so that the pattern moves
+//! with it.
+//!
+//! A checkerboard of "scale" squares across each texture coordinate, or of cubes of side
+//! 1 / "scale" in object space:
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "checker",
+//!     "even": Parameter,   (optional, default 1)
+//!     "odd": Parameter,    (optional, default 0)
+//!     "scale": Float,      (optional, default 8)
+//!     "space": String      (optional, "uv" or "object", default "uv")
+//! }
+//! ```
+//!
+//! A linear gradient along $u$, $v$, or in object space from "start point" to "end 
+//! point":
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "gradient",
+//!     "start": Parameter,              (optional, default 0)
+//!     "end": Parameter,                (optional, default 1)
+//!     "direction": String,             (optional, "u", "v" or "object", default "u")
+//!     "start point": [x, y, z],        (if "direction" is "object")
+//!     "end point": [x, y, z]           (if "direction" is "object")
+//! }
+//! ```
+//!
+//! A blend from "low" to "high" by Perlin noise in object space, varying over distances
+//! of about 1 / "scale". The "pattern" is one of "perlin", "fbm", "turbulence", "marble"
+//! and "wood"; all but "perlin" sum "octaves" layers of noise.
+//! ```
+//! {
+//!     "name": Name1,
+//!     "kind": "noise",
+//!     "low": Parameter,    (optional, default 0)
+//!     "high": Parameter,   (optional, default 1)
+//!     "pattern": String,   (optional, default "perlin")
+//!     "scale": Float,      (optional, default 1)
+//!     "octaves": Integer,  (optional, default 6)
+//!     "seed": Integer      (optional, default 0)
+//! }
+//! ```
 //! 
 //! ## shapes 
 //!
@@ -132,6 +432,37 @@
 //! }
 //! ```
 //!
+//! ### mesh
+//!
+//! A triangle mesh read from a Wavefront OBJ file, whose path is relative to the working
+//! directory. Vertex normals and texture coordinates are used when the file has them.
+//! If "group" is given, only the faces of that group (`g` or `o` in the file) are used,
+//! otherwise all of them are.
+//!
+//! ```
+//! {
+//!     "kind": "mesh",
+//!     "file": String,
+//!     "group": String,   (optional)
+//!     "transform": Transform
+//! }
+//! ```
+//!
+//! ### ply
+//!
+//! A triangle mesh read from a PLY file (ASCII or binary little-endian), whose path is 
+//! relative to the working directory. Vertex normals, texture coordinates and colors are
+//! used when the file has them; the colors can be looked up with the vertex color 
+//! texture.
+//!
+//! ```
+//! {
+//!     "kind": "ply",
+//!     "file": String,
+//!     "transform": Transform
+//! }
+//! ```
+//!
 //! ## transform
 //!
 //! For parsing from the scene file, the value of the field "transform". There are 
@@ -195,7 +526,14 @@
 
 use tracing::instrument;
 
-use crate::{scene::{Scene, SceneInfo}, utility::rng::RandomNumberGenerator};
+use std::sync::Arc;
+use crate::{
+    scene::{Scene, SceneInfo}, 
+    utility::rng::RandomNumberGenerator, 
+    objects::object_group::ObjectGroup,
+    lights::{light_list::LightList, traits::LightLike, area::AreaLight, background::BackgroundLight},
+    backgrounds::traits::BackgroundLike
+};
 use self::{parse_error::ParseError, objects::ObjectParseInfo};
 
 mod camera;
@@ -205,16 +543,30 @@ mod parse_error;
 mod shape;
 mod textures;
 mod materials;
+mod lights;
 mod integrator;
+mod output;
+mod background;
 
 pub fn parse_json(json: &serde_json::Value) -> Result<Scene, ParseError> {
     let camera = camera::new_from_json(&json["camera"])?;
 
     let parsed_integrator = integrator::new_from_json(&json["integrator"])?;
 
+    let scene_lights = lights::parse_json(&json["lights"])?;
+    // Point, spot and directional lights can only be found by sampling them, so without
+    // that the scene would silently render black.
+    if parsed_integrator.skips_light_sampling && !scene_lights.is_empty() {
+        let pe = ParseError {
+            msg: "the lights in 'lights' need the path integrator's 'next event estimation' on".to_string(),
+            json: json["integrator"].clone(),
+        };
+        return Err(pe);
+    }
+
     let objects = {
-        let materials = materials::parse_json(&json["materials"])?;
         let textures = textures::parse_json(&json["textures"])?;
+        let materials = materials::parse_json(&json["materials"], &textures)?;
         
         let info = ObjectParseInfo {
             json: &json["objects"],
@@ -224,6 +576,12 @@ pub fn parse_json(json: &serde_json::Value) -> Result<Scene, ParseError> {
         objects::parse_json(info)?
     };
 
+    let background = background::new_from_json(&json["background"], &json["background color"])?;
+
+    let lights = build_light_list(&objects, &background, scene_lights);
+
+    let output = output::new_from_json(&json["output"])?;
+
     let info = SceneInfo {
         camera,
         integrator: parsed_integrator.integrator,
@@ -231,7 +589,67 @@ pub fn parse_json(json: &serde_json::Value) -> Result<Scene, ParseError> {
         recursive_depth_limit: parsed_integrator.recursion_limit,
         rng: RandomNumberGenerator::from_seed(1),
         objects,
+        lights,
+        background,
+        output,
     };
     Ok(Scene::new(info))
 }
 
+/// Besides the lights listed in the scene, every object with an emissive material is a
+/// light, and so is the background if it asks to be sampled.
+fn build_light_list(
+    objects: &ObjectGroup, 
+    background: &Arc<dyn BackgroundLike>, 
+    mut lights: Vec<Arc<dyn LightLike>>
+) -> LightList {
+    lights.extend(objects.objects().iter()
+        .filter(|object| object.is_emissive())
+        .map(|object| Arc::new(AreaLight::new(object.clone())) as Arc<dyn LightLike>));
+    if background.is_sampled_as_light() {
+        lights.push(Arc::new(BackgroundLight::new(background.clone())));
+    }
+
+    LightList::new_from_vector(lights)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene_with_spot_light(next_event_estimation: bool) -> serde_json::Value {
+        serde_json::json!({
+            "camera": {
+                "resolution": [4, 4],
+                "focal distance": 1,
+                "vertical fov": 90,
+                "aperture radius": 0,
+                "transform": {
+                    "viewer": { "look_from": [0, 1, 1], "look_at": [0, 0, 0], "up_direction": [0, 1, 0] }
+                }
+            },
+            "integrator": { "kind": "path", "next event estimation": next_event_estimation },
+            "lights": [
+                { "kind": "spot", "position": [0, 2, 0], "direction": [0, -1, 0], "intensity": [1, 1, 1] }
+            ],
+            "textures": [ { "name": "white", "kind": "constant", "rgb color": [1, 1, 1] } ],
+            "materials": [ { "name": "lambertian", "kind": "lambertian" } ],
+            "objects": [
+                {
+                    "shape": { "kind": "sphere", "center": [0, -1000, 0], "radius": 1000 },
+                    "texture": "white",
+                    "material": "lambertian"
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn lights_need_next_event_estimation() {
+        assert!(parse_json(&scene_with_spot_light(true)).is_ok());
+
+        let error = parse_json(&scene_with_spot_light(false)).unwrap_err();
+        assert!(error.msg.contains("next event estimation"));
+    }
+}
@@ -1,8 +1,8 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::objects::{object::{Object, ObjectInfo}, object_group::ObjectGroup};
 
-use super::{shape, parse_error::ParseError, textures::TextureMap, materials::MaterialMap};
+use super::{shape::{self, MeshFileCache}, parse_error::ParseError, textures::TextureMap, materials::MaterialMap};
 
 pub struct ObjectParseInfo<'a> {
     pub json: &'a serde_json::Value,
@@ -11,7 +11,7 @@ pub struct ObjectParseInfo<'a> {
 }
 
 pub fn parse_json(info: ObjectParseInfo) -> Result<ObjectGroup, ParseError> {
-    let mut objects_vector: Vec<Rc<Object>> = Vec::new();
+    let mut objects_vector: Vec<Arc<Object>> = Vec::new();
 
     let json_array = match info.json {
         serde_json::Value::Array(arr) => arr,
@@ -24,20 +24,21 @@ pub fn parse_json(info: ObjectParseInfo) -> Result<ObjectGroup, ParseError> {
         }
     };
 
+    let mut mesh_files = MeshFileCache::default();
     for object in json_array.iter() {
         let object_info = ObjectParseInfo {
             json: &object,
             textures: info.textures,
             materials: info.materials
         };
-        objects_vector.push(Rc::new(new_object_from_json(object_info)?));
+        objects_vector.push(Arc::new(new_object_from_json(object_info, &mut mesh_files)?));
     }
 
     Ok(ObjectGroup::new_from_vector(objects_vector))
 }
 
-fn new_object_from_json(info: ObjectParseInfo) -> Result<Object, ParseError> {
-    let shape = shape::new_from_json(&info.json["shape"])?;
+fn new_object_from_json(info: ObjectParseInfo, mesh_files: &mut MeshFileCache) -> Result<Object, ParseError> {
+    let shape = shape::new_from_json(&info.json["shape"], mesh_files)?;
 
     let texture = match info.json["texture"].as_str() {
         Some(texture_name) => info.textures.get(texture_name)?,
@@ -0,0 +1,186 @@
+// S==== IMPORTS {{{1
+
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+use crate::{
+    output::{Output, OutputInfo, CheckpointSchedule},
+    utility::{
+        image::ExrPrecision,
+        post_process::{PostProcess, ToneMapping, TransferFunction},
+        math::float::Float
+    }
+};
+use super::parse_error::ParseError;
+
+// E==== IMPORTS }}}1
+
+const PATH_FIELD_NAME: &str = "path";
+const DEFAULT_PATH: &str = "render.png";
+
+const EXR_PRECISION_FIELD_NAME: &str = "exr precision";
+const HALF_PRECISION: &str = "half";
+const FLOAT_PRECISION: &str = "float";
+const DEFAULT_EXR_PRECISION: ExrPrecision = ExrPrecision::Float;
+
+const EXPOSURE_FIELD_NAME: &str = "exposure";
+const DEFAULT_EXPOSURE: Float = 0.0;
+
+const TONE_MAPPING_FIELD_NAME: &str = "tone mapping";
+const NO_TONE_MAPPING: &str = "none";
+const REINHARD_TONE_MAPPING: &str = "reinhard";
+const ACES_FILMIC_TONE_MAPPING: &str = "aces filmic";
+const DEFAULT_TONE_MAPPING: ToneMapping = ToneMapping::None;
+
+const TRANSFER_FUNCTION_FIELD_NAME: &str = "transfer function";
+const LINEAR_TRANSFER_FUNCTION: &str = "linear";
+const SRGB_TRANSFER_FUNCTION: &str = "srgb";
+
+const CLAMP_FIELD_NAME: &str = "clamp";
+const DEFAULT_CLAMP: bool = false;
+
+const CHECKPOINT_PASSES_FIELD_NAME: &str = "checkpoint passes";
+const CHECKPOINT_SECONDS_FIELD_NAME: &str = "checkpoint seconds";
+
+/// The "output" section is optional, as are all of its fields.
+pub fn new_from_json(json: &serde_json::Value) -> Result<Output, ParseError> {
+    let path = get_field(json, PATH_FIELD_NAME)?
+        .unwrap_or_else(|| DEFAULT_PATH.to_string());
+    let exr_precision = get_exr_precision(json)?;
+
+    let post_process = PostProcess {
+        exposure: get_field(json, EXPOSURE_FIELD_NAME)?.unwrap_or(DEFAULT_EXPOSURE),
+        tone_mapping: get_tone_mapping(json)?,
+        // Replaced when writing, see `Output`.
+        transfer_function: TransferFunction::Linear,
+        clamp: get_field(json, CLAMP_FIELD_NAME)?.unwrap_or(DEFAULT_CLAMP),
+    };
+    let transfer_function = get_transfer_function(json)?;
+    let checkpoints = get_checkpoints(json)?;
+
+    let info = OutputInfo {
+        path,
+        exr_precision,
+        post_process,
+        transfer_function,
+        checkpoints,
+    };
+    Ok(Output::new(info))
+}
+
+/// `None` if the field isn't present.
+fn get_field<T: DeserializeOwned>(json: &serde_json::Value, field_name: &str) -> Result<Option<T>, ParseError> {
+    if json.get(field_name).is_none() {
+        return Ok(None);
+    }
+
+    match serde_json::from_value::<T>(json[field_name].clone()) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}' in 'output'", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn get_exr_precision(json: &serde_json::Value) -> Result<ExrPrecision, ParseError> {
+    let precision = match get_field::<String>(json, EXR_PRECISION_FIELD_NAME)? {
+        Some(precision) => precision,
+        None => { return Ok(DEFAULT_EXR_PRECISION); }
+    };
+
+    match precision.as_str() {
+        HALF_PRECISION => Ok(ExrPrecision::Half),
+        FLOAT_PRECISION => Ok(ExrPrecision::Float),
+        other => {
+            let pe = ParseError {
+                msg: format!("invalid exr precision '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        },
+    }
+}
+
+fn get_tone_mapping(json: &serde_json::Value) -> Result<ToneMapping, ParseError> {
+    let tone_mapping = match get_field::<String>(json, TONE_MAPPING_FIELD_NAME)? {
+        Some(tone_mapping) => tone_mapping,
+        None => { return Ok(DEFAULT_TONE_MAPPING); }
+    };
+
+    match tone_mapping.as_str() {
+        NO_TONE_MAPPING => Ok(ToneMapping::None),
+        REINHARD_TONE_MAPPING => Ok(ToneMapping::Reinhard),
+        ACES_FILMIC_TONE_MAPPING => Ok(ToneMapping::AcesFilmic),
+        other => {
+            let pe = ParseError {
+                msg: format!("invalid tone mapping '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        },
+    }
+}
+
+/// `None` (the default) leaves the choice to `Output`, based on the format.
+fn get_transfer_function(json: &serde_json::Value) -> Result<Option<TransferFunction>, ParseError> {
+    let transfer_function = match get_field::<String>(json, TRANSFER_FUNCTION_FIELD_NAME)? {
+        Some(transfer_function) => transfer_function,
+        None => { return Ok(None); }
+    };
+
+    match transfer_function.as_str() {
+        LINEAR_TRANSFER_FUNCTION => Ok(Some(TransferFunction::Linear)),
+        SRGB_TRANSFER_FUNCTION => Ok(Some(TransferFunction::Srgb)),
+        other => {
+            let pe = ParseError {
+                msg: format!("invalid transfer function '{}'", other),
+                json: json.clone(),
+            };
+            Err(pe)
+        },
+    }
+}
+
+fn get_checkpoints(json: &serde_json::Value) -> Result<CheckpointSchedule, ParseError> {
+    // A checkpoint after every pass (or none at all) is never what was meant.
+    let every_num_passes = match get_field::<u32>(json, CHECKPOINT_PASSES_FIELD_NAME)? {
+        Some(0) => {
+            let pe = ParseError {
+                msg: format!("'{}' must be at least 1", CHECKPOINT_PASSES_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        },
+        num_passes => num_passes,
+    };
+    let every_duration = match get_field::<f64>(json, CHECKPOINT_SECONDS_FIELD_NAME)? {
+        Some(seconds) => match Duration::try_from_secs_f64(seconds) {
+            Ok(duration) if !duration.is_zero() => Some(duration),
+            _ => {
+                let pe = ParseError {
+                    msg: format!("'{}' must be a positive number of seconds", CHECKPOINT_SECONDS_FIELD_NAME),
+                    json: json.clone(),
+                };
+                return Err(pe);
+            }
+        },
+        None => None,
+    };
+
+    Ok(CheckpointSchedule { every_num_passes, every_duration })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_checkpoint_intervals_are_rejected() {
+        assert!(get_checkpoints(&serde_json::json!({ "checkpoint passes": 0 })).is_err());
+        assert!(get_checkpoints(&serde_json::json!({ "checkpoint seconds": 0 })).is_err());
+        assert!(get_checkpoints(&serde_json::json!({ "checkpoint passes": 1, "checkpoint seconds": 0.5 })).is_ok());
+    }
+}
@@ -1,17 +1,22 @@
 
 // S==== IMPORTS {{{1
 
-use std::rc::Rc;
+use std::{sync::Arc, collections::HashMap};
 
 use crate::{
     objects::shapes::{
         traits::ShapeLike, 
         quad::Quad, 
         sphere::{Sphere, SphereInfo}, 
+        mesh::{Mesh, MeshInfo, MeshBuffers, MeshTriangle},
     }, 
-    utility::math::{
-        float::Float, 
-        vector::Vec3
+    utility::{
+        math::{
+            float::Float, 
+            vector::{Vec3, Normal3}
+        },
+        obj::{self, ObjGroup, ObjVertex},
+        ply
     }
 };
 
@@ -25,12 +30,37 @@ use super::{
 const KIND_FIELD_NAME: &str = "kind";
 const QUAD_KIND: &str = "quad";
 const SPHERE_KIND: &str = "sphere";
+const MESH_KIND: &str = "mesh";
+const PLY_KIND: &str = "ply";
+
+const FILE_FIELD_NAME: &str = "file";
+const GROUP_FIELD_NAME: &str = "group";
+
+/// Mesh files that have already been read, by file name, so that all the shapes using
+/// one file (e.g. the groups of an OBJ file) share its vertex data.
+#[derive(Default)]
+pub struct MeshFileCache {
+    obj_files: HashMap<String, CachedObj>,
+    ply_files: HashMap<String, CachedPly>,
+}
+
+struct CachedObj {
+    buffers: Arc<MeshBuffers>,
+    groups: Vec<ObjGroup>,
+}
+
+struct CachedPly {
+    buffers: Arc<MeshBuffers>,
+    triangles: Vec<MeshTriangle>,
+}
 
-pub fn new_from_json(json: &serde_json::Value) -> Result<Rc<dyn ShapeLike>, ParseError> {
+pub fn new_from_json(json: &serde_json::Value, mesh_files: &mut MeshFileCache) -> Result<Arc<dyn ShapeLike>, ParseError> {
     let kind_name = get_kind_name(json)?;
     match kind_name.as_str() {
-        QUAD_KIND => Ok(Rc::new(new_quad_from_json(json)?)),
-        SPHERE_KIND => Ok(Rc::new(new_sphere_from_json(json)?)),
+        QUAD_KIND => Ok(Arc::new(new_quad_from_json(json)?)),
+        SPHERE_KIND => Ok(Arc::new(new_sphere_from_json(json)?)),
+        MESH_KIND => Ok(Arc::new(new_mesh_from_json(json, mesh_files)?)),
+        PLY_KIND => Ok(Arc::new(new_ply_mesh_from_json(json, mesh_files)?)),
         other => { 
             let pe = ParseError {
                 msg: format!("invalid shape kind '{}'", other),
@@ -143,3 +173,197 @@ fn new_sphere_from_json(json: &serde_json::Value) -> Result<Sphere, ParseError>
 
 // E==== SPHERE }}}1
 
+
+// S==== MESH {{{1
+
+fn get_file_name(json: &serde_json::Value) -> Result<String, ParseError> {
+    match &json[FILE_FIELD_NAME] {
+        serde_json::Value::String(s) => Ok(s.to_string()),
+        _ => {
+            let pe = ParseError {
+                msg: format!("could not parse field '{}'", FILE_FIELD_NAME),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+fn new_mesh_from_json(json: &serde_json::Value, mesh_files: &mut MeshFileCache) -> Result<Mesh, ParseError> {
+    let filename = get_file_name(json)?;
+
+    // Default value if none provided.
+    let group_name = match &json[GROUP_FIELD_NAME] {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.to_string()),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' in mesh must be a string", GROUP_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let transform = transform::new_from_json(&json["transform"])?;
+
+    if !mesh_files.obj_files.contains_key(&filename) {
+        let obj_data = obj::load_obj(&filename)
+            .map_err(|msg| ParseError { msg, json: json.clone() })?;
+        let buffers = MeshBuffers {
+            positions: obj_data.positions,
+            normals: obj_data.normals.into_iter().map(Normal3::new_from_vec3).collect(),
+            uvs: obj_data.uvs,
+            colors: Vec::new(),
+        };
+        let cached = CachedObj { buffers: Arc::new(buffers), groups: obj_data.groups };
+        mesh_files.obj_files.insert(filename.clone(), cached);
+    }
+    let obj_file = &mesh_files.obj_files[&filename];
+
+    let triangles = select_obj_triangles(&obj_file.groups, group_name.as_deref())
+        .map_err(|msg| ParseError { msg, json: json.clone() })?;
+
+    let mesh = Mesh::new(MeshInfo {
+        buffers: obj_file.buffers.clone(),
+        triangles,
+        transform,
+    });
+    check_mesh_area(mesh, json)
+}
+
+/// Meshes are sampled by area, so one without any (all of whose triangles are 
+/// degenerate) can't be a light.
+fn check_mesh_area(mesh: Mesh, json: &serde_json::Value) -> Result<Mesh, ParseError> {
+    if mesh.total_area() > 0.0 {
+        return Ok(mesh);
+    }
+
+    let pe = ParseError {
+        msg: "all of the mesh's triangles are degenerate, so it has no area".to_string(),
+        json: json.clone(),
+    };
+    Err(pe)
+}
+
+/// The triangles of the group called `group_name`, or of the whole file if `None`.
+fn select_obj_triangles(groups: &[ObjGroup], group_name: Option<&str>) -> Result<Vec<MeshTriangle>, String> {
+    let groups: Vec<_> = groups.iter()
+        .filter(|group| group_name.is_none_or(|name| group.name == name))
+        .collect();
+
+    if let Some(name) = group_name {
+        if groups.is_empty() {
+            return Err(format!("there is no group '{}' with faces in the file", name));
+        }
+    }
+
+    let triangles: Vec<MeshTriangle> = groups.iter()
+        .flat_map(|group| group.triangles.iter())
+        .map(mesh_triangle_from_obj)
+        .collect();
+
+    if triangles.is_empty() {
+        return Err("the mesh has no faces".to_string());
+    }
+    Ok(triangles)
+}
+
+/// Normals and texture coordinates are dropped unless all three corners have them.
+fn mesh_triangle_from_obj(corners: &[ObjVertex; 3]) -> MeshTriangle {
+    let all_of = |index: fn(&ObjVertex) -> Option<usize>| -> Option<[usize; 3]> {
+        Some([index(&corners[0])?, index(&corners[1])?, index(&corners[2])?])
+    };
+
+    MeshTriangle {
+        positions: corners.clone().map(|corner| corner.position),
+        normals: all_of(|corner| corner.normal),
+        uvs: all_of(|corner| corner.uv),
+        colors: None,
+    }
+}
+
+// E==== MESH }}}1
+
+// S==== PLY {{{1
+
+fn new_ply_mesh_from_json(json: &serde_json::Value, mesh_files: &mut MeshFileCache) -> Result<Mesh, ParseError> {
+    let filename = get_file_name(json)?;
+    let transform = transform::new_from_json(&json["transform"])?;
+
+    if !mesh_files.ply_files.contains_key(&filename) {
+        let ply_data = ply::load_ply(&filename)
+            .map_err(|msg| ParseError { msg, json: json.clone() })?;
+        if ply_data.triangles.is_empty() {
+            let pe = ParseError {
+                msg: "the mesh has no faces".to_string(),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+
+        // Every vertex attribute in a PLY file is indexed like the positions.
+        let triangles = ply_data.triangles.iter()
+            .map(|&indices| MeshTriangle {
+                positions: indices,
+                normals: (!ply_data.normals.is_empty()).then_some(indices),
+                uvs: (!ply_data.uvs.is_empty()).then_some(indices),
+                colors: (!ply_data.colors.is_empty()).then_some(indices),
+            })
+            .collect();
+
+        let buffers = MeshBuffers {
+            positions: ply_data.positions,
+            normals: ply_data.normals.into_iter().map(Normal3::new_from_vec3).collect(),
+            uvs: ply_data.uvs,
+            colors: ply_data.colors,
+        };
+        let cached = CachedPly { buffers: Arc::new(buffers), triangles };
+        mesh_files.ply_files.insert(filename.clone(), cached);
+    }
+    let ply_file = &mesh_files.ply_files[&filename];
+
+    let mesh = Mesh::new(MeshInfo {
+        buffers: ply_file.buffers.clone(),
+        triangles: ply_file.triangles.clone(),
+        transform,
+    });
+    check_mesh_area(mesh, json)
+}
+
+// E==== PLY }}}1
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_of_one_obj_file_share_its_buffers() {
+        let path = std::env::temp_dir().join("mirth_shared_groups_test.obj");
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\ng a\nf 1 2 3\ng b\nf 2 4 3\n";
+        std::fs::write(&path, source).unwrap();
+
+        let mut mesh_files = MeshFileCache::default();
+        let _meshes: Vec<_> = ["a", "b"].iter()
+            .map(|group| {
+                let json = serde_json::json!({ "kind": "mesh", "file": path.to_str().unwrap(), "group": group });
+                new_from_json(&json, &mut mesh_files).unwrap()
+            })
+            .collect();
+
+        assert_eq!(mesh_files.obj_files.len(), 1);
+        let cached = &mesh_files.obj_files[path.to_str().unwrap()];
+        // Held by the cache and by both meshes.
+        assert_eq!(Arc::strong_count(&cached.buffers), 3);
+    }
+
+    #[test]
+    fn meshes_without_area_are_rejected() {
+        let path = std::env::temp_dir().join("mirth_degenerate_test.obj");
+        std::fs::write(&path, "v 0 0 0\nv 1 0 0\nv 2 0 0\nf 1 2 3\n").unwrap();
+
+        let json = serde_json::json!({ "kind": "mesh", "file": path.to_str().unwrap() });
+        let error = new_from_json(&json, &mut MeshFileCache::default()).err().unwrap();
+        assert!(error.msg.contains("no area"));
+    }
+}
@@ -1,9 +1,20 @@
 
 // S==== IMPORTS {{{1
 
-use std::{collections::HashMap, rc::Rc, fmt::format};
+use std::{collections::HashMap, sync::Arc};
 use tracing::error;
-use crate::{utility::math::vector::Color3, objects::textures::{traits::TextureLike, constant::ConstantTexture}};
+use crate::{
+    utility::math::{vector::{Color3, Point3}, float::Float}, 
+    objects::textures::{
+        traits::TextureLike, 
+        constant::ConstantTexture, 
+        vertex_color::VertexColorTexture, 
+        image_texture::{ImageTexture, TextureFilter, WrapMode},
+        checker::{CheckerTexture, CheckerSpace},
+        gradient::{GradientTexture, GradientDirection},
+        noise::{NoiseTexture, NoisePattern}
+    }
+};
 
 use super::parse_error::ParseError;
 
@@ -13,15 +24,64 @@ const NAME_FIELD_NAME: &str = "name";
 
 const KIND_FIELD_NAME: &str = "kind";
 const CONSTANT_KIND: &str = "constant";
+const VERTEX_COLOR_KIND: &str = "vertex color";
+const IMAGE_KIND: &str = "image";
+const CHECKER_KIND: &str = "checker";
+const GRADIENT_KIND: &str = "gradient";
+const NOISE_KIND: &str = "noise";
 
 const RGB_FIELD_NAME: &str = "rgb color";
+const FALLBACK_FIELD_NAME: &str = "fallback rgb color";
+const DEFAULT_FALLBACK: [Float; 3] = [1.0, 1.0, 1.0];
+
+const FILE_FIELD_NAME: &str = "file";
+const FILTER_FIELD_NAME: &str = "filter";
+const NEAREST_FILTER: &str = "nearest";
+const BILINEAR_FILTER: &str = "bilinear";
+const TRILINEAR_FILTER: &str = "trilinear";
+const EWA_FILTER: &str = "ewa";
+const WRAP_FIELD_NAME: &str = "wrap";
+const REPEAT_WRAP: &str = "repeat";
+const CLAMP_WRAP: &str = "clamp";
+const MIRROR_WRAP: &str = "mirror";
+const SRGB_FIELD_NAME: &str = "srgb";
+
+const EVEN_FIELD_NAME: &str = "even";
+const ODD_FIELD_NAME: &str = "odd";
+const SCALE_FIELD_NAME: &str = "scale";
+const DEFAULT_CHECKER_SCALE: Float = 8.0;
+const SPACE_FIELD_NAME: &str = "space";
+const UV_SPACE: &str = "uv";
+const OBJECT_SPACE: &str = "object";
+
+const START_FIELD_NAME: &str = "start";
+const END_FIELD_NAME: &str = "end";
+const DIRECTION_FIELD_NAME: &str = "direction";
+const U_DIRECTION: &str = "u";
+const V_DIRECTION: &str = "v";
+const START_POINT_FIELD_NAME: &str = "start point";
+const END_POINT_FIELD_NAME: &str = "end point";
+
+const LOW_FIELD_NAME: &str = "low";
+const HIGH_FIELD_NAME: &str = "high";
+const PATTERN_FIELD_NAME: &str = "pattern";
+const PERLIN_PATTERN: &str = "perlin";
+const FBM_PATTERN: &str = "fbm";
+const TURBULENCE_PATTERN: &str = "turbulence";
+const MARBLE_PATTERN: &str = "marble";
+const WOOD_PATTERN: &str = "wood";
+const DEFAULT_NOISE_SCALE: Float = 1.0;
+const OCTAVES_FIELD_NAME: &str = "octaves";
+const DEFAULT_OCTAVES: u32 = 6;
+const SEED_FIELD_NAME: &str = "seed";
+const DEFAULT_SEED: u32 = 0;
 
 pub struct TextureMap {
-    map: HashMap<String, Rc<dyn TextureLike>>
+    map: HashMap<String, Arc<dyn TextureLike>>
 }
 
 impl TextureMap {
-    pub fn get(&self, key: &str) -> Result<Rc<dyn TextureLike>, ParseError> {
+    pub fn get(&self, key: &str) -> Result<Arc<dyn TextureLike>, ParseError> {
         match self.map.get(key) {
             Some(val) => Ok(val.clone()),
             None => {
@@ -47,23 +107,32 @@ pub fn parse_json(json: &serde_json::Value) -> Result<TextureMap, ParseError> {
         }
     };   
 
-    let mut to_return: HashMap<String, Rc<dyn TextureLike>> = HashMap::new();
+    // Textures may take other textures as inputs, which have to be listed before them.
+    let mut to_return = TextureMap {
+        map: HashMap::new()
+    };
     for texture in json_array.iter() {
-        let result = parse_single_texture(&texture)?;
-        to_return.insert(result.0, result.1);
+        let result = parse_single_texture(texture, &to_return)?;
+        to_return.map.insert(result.0, result.1);
     }
 
-    Ok(TextureMap {
-        map: to_return
-    })
+    Ok(to_return)
 }
 
-fn parse_single_texture(json: &serde_json::Value) -> Result<(String, Rc<dyn TextureLike>), ParseError> {
+fn parse_single_texture(
+    json: &serde_json::Value, 
+    textures: &TextureMap
+) -> Result<(String, Arc<dyn TextureLike>), ParseError> {
     let name = get_name(json)?;
 
     let kind_name = get_kind_name(json)?; 
     match kind_name.as_str() {
         CONSTANT_KIND => { return Ok((name.to_owned(), parse_constant_texture(json)?)) },
+        VERTEX_COLOR_KIND => Ok((name.to_owned(), parse_vertex_color_texture(json)?)),
+        IMAGE_KIND => Ok((name.to_owned(), parse_image_texture(json)?)),
+        CHECKER_KIND => Ok((name.to_owned(), parse_checker_texture(json, textures)?)),
+        GRADIENT_KIND => Ok((name.to_owned(), parse_gradient_texture(json, textures)?)),
+        NOISE_KIND => Ok((name.to_owned(), parse_noise_texture(json, textures)?)),
         other => {
             let pe = ParseError {
                 msg: format!("unknown texture kind '{}'", other), 
@@ -114,7 +183,7 @@ fn get_kind_name(json: &serde_json::Value) -> Result<String, ParseError> {
     };
 }
 
-fn parse_constant_texture(json: &serde_json::Value) -> Result<Rc<ConstantTexture>, ParseError> {
+fn parse_constant_texture(json: &serde_json::Value) -> Result<Arc<ConstantTexture>, ParseError> {
     let rgb_color = match serde_json::from_value::<Color3>(json[RGB_FIELD_NAME].clone()) {
         Ok(c) => c,
         Err(_) => {
@@ -127,7 +196,211 @@ fn parse_constant_texture(json: &serde_json::Value) -> Result<Rc<ConstantTexture
     };
 
     let texture = ConstantTexture::new_from_rgb(rgb_color);
-    Ok(Rc::new(texture))
+    Ok(Arc::new(texture))
+}
+
+fn parse_vertex_color_texture(json: &serde_json::Value) -> Result<Arc<VertexColorTexture>, ParseError> {
+    // Default value if none provided.
+    if json.get(FALLBACK_FIELD_NAME).is_none() {
+        let [r, g, b] = DEFAULT_FALLBACK;
+        return Ok(Arc::new(VertexColorTexture::new(Color3::new(r, g, b))));
+    }
+
+    let fallback = match serde_json::from_value::<Color3>(json[FALLBACK_FIELD_NAME].clone()) {
+        Ok(c) => c,
+        Err(_) => {
+            let pe = ParseError {
+                msg: "couldn't parse this vertex color texture's fallback RGB color".to_string(),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    Ok(Arc::new(VertexColorTexture::new(fallback)))
+}
+
+fn parse_image_texture(json: &serde_json::Value) -> Result<Arc<ImageTexture>, ParseError> {
+    let filename = match &json[FILE_FIELD_NAME] {
+        serde_json::Value::String(s) => s.to_string(),
+        _ => {
+            let pe = ParseError {
+                msg: format!("image texture requires a file name in field '{}'", FILE_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    // Default values if none provided.
+    let filters = [NEAREST_FILTER, BILINEAR_FILTER, TRILINEAR_FILTER, EWA_FILTER];
+    let filter = match get_option(json, FILTER_FIELD_NAME, &filters)? {
+        Some(NEAREST_FILTER) => TextureFilter::Nearest,
+        Some(BILINEAR_FILTER) => TextureFilter::Bilinear,
+        Some(EWA_FILTER) => TextureFilter::Ewa,
+        _ => TextureFilter::Trilinear,
+    };
+    let wrap_mode = match get_option(json, WRAP_FIELD_NAME, &[REPEAT_WRAP, CLAMP_WRAP, MIRROR_WRAP])? {
+        Some(CLAMP_WRAP) => WrapMode::Clamp,
+        Some(MIRROR_WRAP) => WrapMode::Mirror,
+        _ => WrapMode::Repeat,
+    };
+    let srgb = match &json[SRGB_FIELD_NAME] {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(b) => Some(*b),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' in texture must be a boolean", SRGB_FIELD_NAME),
+                json: json.clone(),
+            };
+            return Err(pe);
+        }
+    };
+
+    let texture = ImageTexture::load(&filename, filter, wrap_mode, srgb)
+        .map_err(|msg| ParseError { msg, json: json.clone() })?;
+    Ok(Arc::new(texture))
+}
+
+/// The texture in `field_name`, given either by name or as a constant number or RGB 
+/// color. If there is none, a constant texture of `default`.
+pub fn get_texture_parameter(
+    json: &serde_json::Value, 
+    field_name: &str, 
+    default: Float, 
+    textures: &TextureMap
+) -> Result<Arc<dyn TextureLike>, ParseError> {
+    let constant = |value: Float| -> Arc<dyn TextureLike> {
+        Arc::new(ConstantTexture::new_from_rgb(Color3::new(value, value, value)))
+    };
+
+    match json.get(field_name) {
+        // Default value if none provided.
+        None => Ok(constant(default)),
+        Some(serde_json::Value::String(texture_name)) => textures.get(texture_name),
+        Some(serde_json::Value::Number(n)) if n.as_f64().is_some() => {
+            Ok(constant(n.as_f64().unwrap() as Float))
+        },
+        Some(value) => match serde_json::from_value::<Color3>(value.clone()) {
+            Ok(c) => Ok(Arc::new(ConstantTexture::new_from_rgb(c))),
+            Err(_) => {
+                let pe = ParseError {
+                    msg: format!("field '{}' must be a number, an RGB color or the name of a texture", field_name),
+                    json: json.clone(),
+                };
+                Err(pe)
+            }
+        }
+    }
+}
+
+/// The string in `field_name`, which must be one of `options`, or `None` if there is none.
+fn get_option<'a>(
+    json: &serde_json::Value, 
+    field_name: &str, 
+    options: &[&'a str]
+) -> Result<Option<&'a str>, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::String(s) if options.contains(&s.as_str()) => {
+            Ok(options.iter().find(|option| *option == s).copied())
+        },
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' in texture must be one of {:?}", field_name, options),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+/// The number in `field_name`, or `default` if there is none.
+fn get_float(json: &serde_json::Value, field_name: &str, default: Float) -> Result<Float, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(default),
+        serde_json::Value::Number(n) if n.as_f64().is_some() => Ok(n.as_f64().unwrap() as Float),
+        _ => {
+            let pe = ParseError {
+                msg: format!("value of field '{}' in texture must be a number", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    }
+}
+
+/// The non-negative integer in `field_name`, or `default` if there is none.
+fn get_u32(json: &serde_json::Value, field_name: &str, default: u32) -> Result<u32, ParseError> {
+    match &json[field_name] {
+        serde_json::Value::Null => Ok(default),
+        value => match value.as_u64().and_then(|n| u32::try_from(n).ok()) {
+            Some(n) => Ok(n),
+            None => {
+                let pe = ParseError {
+                    msg: format!("value of field '{}' in texture must be a non-negative integer", field_name),
+                    json: json.clone(),
+                };
+                Err(pe)
+            }
+        }
+    }
+}
+
+fn parse_checker_texture(json: &serde_json::Value, textures: &TextureMap) -> Result<Arc<CheckerTexture>, ParseError> {
+    let even = get_texture_parameter(json, EVEN_FIELD_NAME, 1.0, textures)?;
+    let odd = get_texture_parameter(json, ODD_FIELD_NAME, 0.0, textures)?;
+    let scale = get_float(json, SCALE_FIELD_NAME, DEFAULT_CHECKER_SCALE)?;
+    let space = match get_option(json, SPACE_FIELD_NAME, &[UV_SPACE, OBJECT_SPACE])? {
+        Some(OBJECT_SPACE) => CheckerSpace::Object,
+        _ => CheckerSpace::Uv,
+    };
+
+    Ok(Arc::new(CheckerTexture::new(even, odd, scale, space)))
+}
+
+fn parse_gradient_texture(json: &serde_json::Value, textures: &TextureMap) -> Result<Arc<GradientTexture>, ParseError> {
+    let start = get_texture_parameter(json, START_FIELD_NAME, 0.0, textures)?;
+    let end = get_texture_parameter(json, END_FIELD_NAME, 1.0, textures)?;
+
+    let get_point = |field_name: &str| match serde_json::from_value::<Point3>(json[field_name].clone()) {
+        Ok(p) => Ok(p),
+        Err(_) => {
+            let pe = ParseError {
+                msg: format!("gradient in object space requires a point in field '{}'", field_name),
+                json: json.clone(),
+            };
+            Err(pe)
+        }
+    };
+    let direction = match get_option(json, DIRECTION_FIELD_NAME, &[U_DIRECTION, V_DIRECTION, OBJECT_SPACE])? {
+        Some(V_DIRECTION) => GradientDirection::V,
+        Some(OBJECT_SPACE) => GradientDirection::Object {
+            start: get_point(START_POINT_FIELD_NAME)?,
+            end: get_point(END_POINT_FIELD_NAME)?,
+        },
+        _ => GradientDirection::U,
+    };
+
+    Ok(Arc::new(GradientTexture::new(start, end, direction)))
+}
+
+fn parse_noise_texture(json: &serde_json::Value, textures: &TextureMap) -> Result<Arc<NoiseTexture>, ParseError> {
+    let low = get_texture_parameter(json, LOW_FIELD_NAME, 0.0, textures)?;
+    let high = get_texture_parameter(json, HIGH_FIELD_NAME, 1.0, textures)?;
+    let patterns = [PERLIN_PATTERN, FBM_PATTERN, TURBULENCE_PATTERN, MARBLE_PATTERN, WOOD_PATTERN];
+    let pattern = match get_option(json, PATTERN_FIELD_NAME, &patterns)? {
+        Some(FBM_PATTERN) => NoisePattern::Fbm,
+        Some(TURBULENCE_PATTERN) => NoisePattern::Turbulence,
+        Some(MARBLE_PATTERN) => NoisePattern::Marble,
+        Some(WOOD_PATTERN) => NoisePattern::Wood,
+        _ => NoisePattern::Perlin,
+    };
+    let scale = get_float(json, SCALE_FIELD_NAME, DEFAULT_NOISE_SCALE)?;
+    let octaves = get_u32(json, OCTAVES_FIELD_NAME, DEFAULT_OCTAVES)?;
+    let seed = get_u32(json, SEED_FIELD_NAME, DEFAULT_SEED)?;
+
+    Ok(Arc::new(NoiseTexture::new(low, high, pattern, scale, octaves, seed)))
 }
 
 // S==== TESTS {{{1
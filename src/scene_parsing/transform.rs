@@ -160,7 +160,7 @@ fn new_from_simple_sequence_json(json: &serde_json::Value) -> Result<Transform,
                 }
                 let simple_scale = parsed.unwrap();
 
-                sequence.push(Matrix4TransformKind::Translation(simple_scale));
+                sequence.push(Matrix4TransformKind::Scale(simple_scale));
             }
             other => {
                 let parse_error = ParseError {
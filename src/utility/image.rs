@@ -1,8 +1,10 @@
 //! Reading and writting to image formats
 
-use image;
+use std::{fs::File, io::{BufReader, BufWriter, Write}, path::Path};
+use image::{self, codecs::hdr::{HdrDecoder, HdrEncoder}, DynamicImage};
+use half::f16;
 use serde::Deserialize;
-use super::math::{vector::Color3, float::Float};
+use super::{math::{vector::Color3, float::Float}, post_process::srgb_oetf_inverse};
 
 // S==== ASSOCIATED TYPES {{{1
 
@@ -12,15 +14,34 @@ pub struct Resolution {
     pub height: u32,
 }
 
+impl Resolution {
+    /// Covers the image with tiles of (at most) `tile_size` by `tile_size` pixels, row 
+    /// by row starting from the bottom left. Tiles along the top and right edges are cut 
+    /// short to fit.
+    pub fn tiles(&self, tile_size: u32) -> Vec<Tile> {
+        let mut to_return: Vec<Tile> = Vec::new();
+
+        for y in (0..self.height).step_by(tile_size as usize) {
+            for x in (0..self.width).step_by(tile_size as usize) {
+                to_return.push(Tile {
+                    x,
+                    y,
+                    width: u32::min(tile_size, self.width - x),
+                    height: u32::min(tile_size, self.height - y),
+                });
+            }
+        }
+
+        to_return
+    }
+}
+
 impl IntoIterator for Resolution {
     type Item = Pixel;
     type IntoIter = PixelIterator;
 
     fn into_iter(self) -> Self::IntoIter {
-        PixelIterator {
-            pixel: Pixel { x: 0, y: 0 },
-            resolution: self.clone(),
-        }
+        Tile { x: 0, y: 0, width: self.width, height: self.height }.pixels()
     }
 }
 
@@ -30,29 +51,58 @@ pub struct Pixel {
     pub y: u32,
 }
 
+/// A rectangular block of pixels, whose bottom left pixel is (`x`, `y`).
+#[derive(Clone, Debug)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Tile {
+    /// The pixels of the tile, row by row starting from the bottom left.
+    pub fn pixels(&self) -> PixelIterator {
+        PixelIterator {
+            tile: self.clone(),
+            next_index: 0,
+        }
+    }
+}
+
 pub struct PixelIterator {
-    pixel: Pixel,
-    resolution: Resolution,
+    tile: Tile,
+    /// The position of the next pixel in the order we go through them.
+    next_index: u32,
 }
 
 impl Iterator for PixelIterator {
     type Item = Pixel;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pixel.x < self.resolution.width - 1 { 
-            self.pixel = Pixel { x: self.pixel.x + 1, y: self.pixel.y };
-            return Some(self.pixel.clone());
+        if self.next_index >= self.tile.width * self.tile.height {
+            return None;
         }
 
-        if self.pixel.y < self.resolution.height - 1 {
-            self.pixel = Pixel { x: 0, y: self.pixel.y + 1 };
-            return Some(self.pixel.clone());
-        }
+        let pixel = Pixel {
+            x: self.tile.x + self.next_index % self.tile.width,
+            y: self.tile.y + self.next_index / self.tile.width,
+        };
+        self.next_index += 1;
 
-        return None;
+        Some(pixel)
     }
 }
 
+/// The precision of the channels of an OpenEXR file.
+#[derive(Clone, Copy, Debug)]
+pub enum ExrPrecision {
+    /// 16-bit floats, which are plenty for display and compositing, at half the size.
+    Half,
+    /// 32-bit floats, exactly as rendered.
+    Float,
+}
+
 // E==== ASSOCIATED TYPES }}}1
 
 // S==== IMAGE {{{1
@@ -79,6 +129,10 @@ impl Image {
         }
     }
 
+    pub fn get_resolution(&self) -> Resolution {
+        self.resolution.clone()
+    }
+
     /// Set the pixel (x, y) to color. Recall that (0, 0) is in the bottom left.
     pub fn set_pixel_color(&mut self, pixel: &Pixel, color: Color3) {
         assert!(pixel.x < self.resolution.width && pixel.y < self.resolution.height, "out of bounds index");
@@ -101,16 +155,102 @@ impl Image {
         Color3::new(pre_color.0[0], pre_color.0[1], pre_color.0[2])
     }
 
-    /// Saves the image buffer to a file, whose encoding is deduced from the filename 
-    /// (so include the extension in `filename`).
+    /// Saves the image to a file, whose encoding is deduced from the filename's extension:
+    ///
+    /// - `.png`: 8 bits per channel. The values are stored as they are, clamped to 
+    ///   $[0, 1]$, so they should already be encoded for display (see `post_process`).
+    /// - `.hdr`: Radiance RGBE, which keeps the (linear) floating point values.
+    /// - `.exr`: OpenEXR, with 32-bit float channels (see `save_to_exr` for half floats).
+    /// - `.pfm`: Portable float map.
+    ///
+    /// The last two store the linear values exactly.
     pub fn save_to_file(&self, filename: &str) -> Result<(), String> {
-        match self.internal.save(filename) {
-            Ok(_) => { return Ok(()); }
-            Err(e) => { return Err(e.to_string()); }
+        let extension = Path::new(filename)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        match extension.as_deref() {
+            Some("png") => self.save_to_png(filename),
+            Some("hdr") => self.save_to_hdr(filename),
+            Some("exr") => self.save_to_exr(filename, ExrPrecision::Float),
+            Some("pfm") => self.save_to_pfm(filename),
+            _ => Err(format!(
+                "can't deduce the image format of '{}' (supported extensions are .png, .hdr, .exr and .pfm)",
+                filename
+            )),
         }
     }
+
+    fn save_to_png(&self, filename: &str) -> Result<(), String> {
+        let encoded = image::RgbImage::from_fn(
+            self.internal.width(), 
+            self.internal.height(), 
+            |x, y| {
+                let color = self.internal.get_pixel(x, y);
+                image::Rgb(color.0.map(|channel| {
+                    (Float::clamp(channel, 0.0, 1.0) * 255.0).round() as u8
+                }))
+            }
+        );
+
+        encoded.save(filename).map_err(|e| e.to_string())
+    }
+
+    fn save_to_hdr(&self, filename: &str) -> Result<(), String> {
+        let file = File::create(filename).map_err(|e| e.to_string())?;
+        let pixels: Vec<image::Rgb<f32>> = self.internal.pixels().cloned().collect();
+
+        HdrEncoder::new(BufWriter::new(file))
+            .encode(&pixels, self.internal.width() as usize, self.internal.height() as usize)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Saves the image as an OpenEXR file with RGB channels of the given precision.
+    pub fn save_to_exr(&self, filename: &str, precision: ExrPrecision) -> Result<(), String> {
+        let width = self.internal.width() as usize;
+        let height = self.internal.height() as usize;
+        // Both crates put (0, 0) in the top left corner.
+        let channels_at = |x: usize, y: usize| self.internal.get_pixel(x as u32, y as u32).0;
+
+        let result = match precision {
+            ExrPrecision::Half => exr::prelude::write_rgb_file(filename, width, height, |x, y| {
+                let [r, g, b] = channels_at(x, y);
+                (f16::from_f32(r), f16::from_f32(g), f16::from_f32(b))
+            }),
+            ExrPrecision::Float => exr::prelude::write_rgb_file(filename, width, height, |x, y| {
+                let [r, g, b] = channels_at(x, y);
+                (r, g, b)
+            }),
+        };
+
+        result.map_err(|e| e.to_string())
+    }
+
+    /// Saves the image as a (color) portable float map: a short text header followed by
+    /// the rows of little-endian 32-bit floats, starting from the bottom row.
+    pub fn save_to_pfm(&self, filename: &str) -> Result<(), String> {
+        let file = File::create(filename).map_err(|e| e.to_string())?;
+        let mut writer = BufWriter::new(file);
+        let width = self.resolution.width;
+        let height = self.resolution.height;
+
+        // A negative scale marks the data as little-endian.
+        write!(writer, "PF\n{} {}\n-1.0\n", width, height).map_err(|e| e.to_string())?;
+        // The image crate counts rows from the top.
+        for y in (0..height).rev() {
+            for x in 0..width {
+                for channel in self.internal.get_pixel(x, y).0 {
+                    writer.write_all(&channel.to_le_bytes()).map_err(|e| e.to_string())?;
+                }
+            }
+        }
+
+        writer.flush().map_err(|e| e.to_string())
+    }
 }
 
+
 // E==== IMAGE }}}1
 
 // S==== IMAGE BUFFER {{{1
@@ -162,10 +302,87 @@ impl ImageBuffer {
         let image = self.average_samples();
         image.save_to_file(filename)
     }
+
+    /// Saves the averaged samples as an OpenEXR file (see `Image::save_to_exr`).
+    pub fn save_to_exr(&self, filename: &str, precision: ExrPrecision) -> Result<(), String> {
+        self.average_samples().save_to_exr(filename, precision)
+    }
+
+    /// Saves the averaged samples as a portable float map (see `Image::save_to_pfm`).
+    pub fn save_to_pfm(&self, filename: &str) -> Result<(), String> {
+        self.average_samples().save_to_pfm(filename)
+    }
 }
 
 // E==== IMAGE BUFFER }}}1
 
+// S==== DECODED IMAGE {{{1
+
+/// The linear colors of an image read from a file, for looking up (e.g. as a texture)
+/// rather than writing to.
+pub struct DecodedImage {
+    pub width: usize,
+    pub height: usize,
+    /// Row by row starting from the top of the image, as stored in files.
+    pub texels: Vec<Color3>,
+}
+
+impl DecodedImage {
+    /// Reads an image file in any format the `image` crate supports, such as PNG, JPEG
+    /// or Radiance HDR. Whether the stored values are sRGB encoded is given by `srgb`,
+    /// which by default is the case for all but floating point images. The alpha
+    /// channel, if any, is ignored.
+    pub fn load(filename: &str, srgb: Option<bool>) -> Result<Self, String> {
+        let read_error = |e: image::ImageError| format!("could not read image '{}': {}", filename, e);
+
+        // `image::open()` would squeeze Radiance HDR files into 8 bits per channel.
+        let extension = Path::new(filename).extension().and_then(|e| e.to_str());
+        if extension.is_some_and(|e| e.eq_ignore_ascii_case("hdr")) {
+            let file = File::open(filename).map_err(|e| format!("could not open '{}': {}", filename, e))?;
+            let decoder = HdrDecoder::new(BufReader::new(file)).map_err(read_error)?;
+            let metadata = decoder.metadata();
+            let pixels = decoder.read_image_hdr().map_err(read_error)?;
+            if pixels.is_empty() {
+                return Err(format!("image '{}' is empty", filename));
+            }
+            let decode = |value: f32| if srgb.unwrap_or(false) { srgb_oetf_inverse(value as Float) } else { value as Float };
+
+            return Ok(Self {
+                width: metadata.width as usize,
+                height: metadata.height as usize,
+                texels: pixels.iter()
+                    .map(|pixel| Color3::new(decode(pixel[0]), decode(pixel[1]), decode(pixel[2])))
+                    .collect(),
+            });
+        }
+
+        let image = image::open(filename).map_err(read_error)?;
+        if image.width() == 0 || image.height() == 0 {
+            return Err(format!("image '{}' is empty", filename));
+        }
+
+        let is_floating_point = matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_));
+        let decode = if srgb.unwrap_or(!is_floating_point) {
+            |value: f32| srgb_oetf_inverse(value as Float)
+        } else {
+            |value: f32| value as Float
+        };
+
+        let rgb = image.to_rgb32f();
+        let texels = rgb.pixels()
+            .map(|pixel| Color3::new(decode(pixel[0]), decode(pixel[1]), decode(pixel[2])))
+            .collect();
+
+        Ok(Self {
+            width: rgb.width() as usize,
+            height: rgb.height() as usize,
+            texels,
+        })
+    }
+}
+
+// E==== DECODED IMAGE }}}1
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,5 +395,56 @@ mod tests {
         }
         print!("\n");
     }
+
+    #[test]
+    fn tiles_cover_every_pixel_once() {
+        let resolution = Resolution { width: 37, height: 20 };
+        let mut times_covered = vec![0; 37 * 20];
+
+        for tile in resolution.tiles(16).iter() {
+            for pixel in tile.pixels() {
+                times_covered[(pixel.y * 37 + pixel.x) as usize] += 1;
+            }
+        }
+
+        assert!(times_covered.iter().all(|&count| count == 1));
+        assert_eq!(resolution.into_iter().count(), 37 * 20);
+    }
+
+    #[test]
+    fn pfm_stores_unclamped_rows_from_the_bottom() {
+        let mut image = Image::new(Resolution { width: 2, height: 2 });
+        image.set_pixel_color(&Pixel { x: 0, y: 0 }, Color3::new(12.5, -1.0, 0.25));
+        image.set_pixel_color(&Pixel { x: 1, y: 1 }, Color3::new(3.0, 2.0, 1.0));
+
+        let path = std::env::temp_dir().join("mirth_pfm_test.pfm");
+        image.save_to_pfm(path.to_str().unwrap()).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(&bytes[..header.len()], header);
+
+        let floats: Vec<f32> = bytes[header.len()..]
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        assert_eq!(floats.len(), 12);
+        assert_eq!(&floats[0..3], &[12.5, -1.0, 0.25]);
+        assert_eq!(&floats[9..12], &[3.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn hdr_files_keep_values_above_one_whatever_the_case_of_the_extension() {
+        let mut image = Image::new(Resolution { width: 2, height: 1 });
+        image.set_pixel_color(&Pixel { x: 0, y: 0 }, Color3::new(40.0, 2.0, 0.5));
+
+        let path = std::env::temp_dir().join("mirth_decode_test.HDR");
+        image.save_to_hdr(path.to_str().unwrap()).unwrap();
+        let decoded = DecodedImage::load(path.to_str().unwrap(), None).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (2, 1));
+        let texel = &decoded.texels[0];
+        assert!((texel.x() - 40.0).abs() < 0.5 && (texel.y() - 2.0).abs() < 0.05);
+    }
 }
 
@@ -0,0 +1,50 @@
+//! Lets a render be stopped early with Ctrl-C (`SIGINT`), keeping what was rendered so 
+//! far. Once the handler is installed, the first Ctrl-C only sets a flag, which the 
+//! renderer checks between passes. A second Ctrl-C terminates the program as usual.
+//!
+//! We only support this on Unix, where it takes a single call into the C library.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl-C has been pressed since `install_handler` was called.
+pub fn is_requested() -> bool {
+    INTERRUPT_REQUESTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+pub fn install_handler() {
+    unix::install_handler();
+}
+
+#[cfg(not(unix))]
+pub fn install_handler() {}
+
+#[cfg(unix)]
+mod unix {
+    use std::{os::raw::c_int, sync::atomic::Ordering};
+    use super::INTERRUPT_REQUESTED;
+
+    const SIGINT: c_int = 2;
+    /// The default disposition, as a `sighandler_t`.
+    const SIG_DFL: usize = 0;
+
+    extern "C" {
+        /// The handler is a `sighandler_t`, which is either a function pointer or one of
+        /// the special values like `SIG_DFL`.
+        fn signal(signum: c_int, handler: usize) -> usize;
+    }
+
+    /// Only does what is allowed in a signal handler: an atomic store, and `signal`
+    /// itself, which restores the default so that the next Ctrl-C exits.
+    extern "C" fn handle_interrupt(_signum: c_int) {
+        INTERRUPT_REQUESTED.store(true, Ordering::SeqCst);
+        unsafe { signal(SIGINT, SIG_DFL); }
+    }
+
+    pub fn install_handler() {
+        let handler: extern "C" fn(c_int) = handle_interrupt;
+        unsafe { signal(SIGINT, handler as usize); }
+    }
+}
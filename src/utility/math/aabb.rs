@@ -0,0 +1,160 @@
+use super::{vector::{Point3, Vec3}, float::Float, ray::Ray3};
+
+/// An axis-aligned bounding box, described by its minimum and maximum corners.
+#[derive(Clone, Debug)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    pub fn new(min: Point3, max: Point3) -> Self {
+        Self { min, max }
+    }
+
+    /// A box containing nothing. Its union with any other box is that other box.
+    pub fn empty() -> Self {
+        Self {
+            min: Point3::new(Float::INFINITY, Float::INFINITY, Float::INFINITY),
+            max: Point3::new(Float::NEG_INFINITY, Float::NEG_INFINITY, Float::NEG_INFINITY),
+        }
+    }
+
+    /// The smallest box containing all of `points`.
+    pub fn new_from_points(points: &[Point3]) -> Self {
+        points.iter().fold(Self::empty(), |aabb, point| aabb.union_point(point))
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3::new(
+                Float::min(self.min.x(), other.min.x()),
+                Float::min(self.min.y(), other.min.y()),
+                Float::min(self.min.z(), other.min.z())
+            ),
+            max: Point3::new(
+                Float::max(self.max.x(), other.max.x()),
+                Float::max(self.max.y(), other.max.y()),
+                Float::max(self.max.z(), other.max.z())
+            ),
+        }
+    }
+
+    pub fn union_point(&self, point: &Point3) -> Aabb {
+        self.union(&Aabb::new(point.clone(), point.clone()))
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (&self.min + &self.max)
+    }
+
+    /// The vector from the minimum to the maximum corner.
+    pub fn diagonal(&self) -> Vec3 {
+        &self.max - &self.min
+    }
+
+    /// The index (0 for $x$, 1 for $y$, 2 for $z$) of the axis along which the box is 
+    /// longest.
+    pub fn longest_axis(&self) -> usize {
+        let diagonal = self.diagonal();
+
+        if diagonal.x() > diagonal.y() && diagonal.x() > diagonal.z() { 0 }
+        else if diagonal.y() > diagonal.z() { 1 }
+        else { 2 }
+    }
+
+    pub fn surface_area(&self) -> Float {
+        let d = self.diagonal();
+        if d.x() < 0.0 || d.y() < 0.0 || d.z() < 0.0 {
+            return 0.0;
+        }
+
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    /// Whether the ray passes through the box for some $t$ in its range.
+    pub fn intersects(&self, ray: &Ray3) -> bool {
+        self.hit_interval(ray, &inverse_direction_of(ray)).is_some()
+    }
+
+    /// The range of $t$ (within the ray's range) for which the ray is inside the box, if
+    /// it is nonempty. This is the standard slab test: we clip the ray's range against the
+    /// pair of planes bounding the box along each axis in turn. 
+    ///
+    /// `inverse_direction` is the componentwise reciprocal of the ray's direction (see 
+    /// [`inverse_direction_of`]), which is worth computing once when testing the same ray
+    /// against many boxes. Zero components of the direction give infinite reciprocals,
+    /// which work out, except when the origin also lies on one of the planes; the 
+    /// resulting NaN is ignored by `Float::max` and `Float::min`, so we treat that as a 
+    /// hit.
+    pub fn hit_interval(&self, ray: &Ray3, inverse_direction: &Vec3) -> Option<(Float, Float)> {
+        let mut t_min = ray.min_t;
+        let mut t_max = ray.max_t;
+
+        for axis in 0..3 {
+            let inverse = inverse_direction.component(axis);
+            let mut t0 = (self.min.component(axis) - ray.origin.component(axis)) * inverse;
+            let mut t1 = (self.max.component(axis) - ray.origin.component(axis)) * inverse;
+            if inverse < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            t_min = Float::max(t_min, t0);
+            t_max = Float::min(t_max, t1);
+            if t_max < t_min {
+                return None;
+            }
+        }
+
+        Some((t_min, t_max))
+    }
+}
+
+/// The componentwise reciprocal of the ray's direction, for use with 
+/// [`Aabb::hit_interval`].
+pub fn inverse_direction_of(ray: &Ray3) -> Vec3 {
+    Vec3::new(
+        1.0 / ray.direction.x(),
+        1.0 / ray.direction.y(),
+        1.0 / ray.direction.z()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn hit_interval_is_clipped_to_box() {
+        let ray = Ray3::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let (t_min, t_max) = unit_box().hit_interval(&ray, &inverse_direction_of(&ray)).unwrap();
+
+        assert!((t_min - 1.0).abs() < 1e-6);
+        assert!((t_max - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn misses_and_respects_ray_range() {
+        let passing_by = Ray3::new(Point3::new(-1.0, 2.0, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        assert!(!unit_box().intersects(&passing_by));
+
+        let pointing_away = Ray3::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!unit_box().intersects(&pointing_away));
+
+        let mut too_short = Ray3::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        too_short.max_t = 0.5;
+        assert!(!unit_box().intersects(&too_short));
+    }
+
+    #[test]
+    fn flat_box_is_hit() {
+        let flat = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 0.0));
+        let ray = Ray3::new(Point3::new(0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(flat.intersects(&ray));
+    }
+}
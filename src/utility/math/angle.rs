@@ -14,13 +14,13 @@ impl Angle {
     pub fn as_degrees(&self) -> Float {
         match self.units {
             AngleUnits::Degrees => self.amount,
-            AngleUnits::Radians => Float::to_radians(self.amount),
+            AngleUnits::Radians => Float::to_degrees(self.amount),
         }
     }
 
     pub fn as_radians(&self) -> Float {
         match self.units {
-            AngleUnits::Degrees => Float::to_degrees(self.amount),
+            AngleUnits::Degrees => Float::to_radians(self.amount),
             AngleUnits::Radians => self.amount,
         }
     }
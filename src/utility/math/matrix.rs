@@ -27,6 +27,21 @@ impl Matrix4 {
 
         Vec3::new(xformed_vec.x, xformed_vec.y, xformed_vec.z)
     }
+
+    /// Transforms `vector` by the transpose of (the linear part of) this matrix.
+    pub fn transform_vector_by_transpose(&self, vector: &Vec3) -> Vec3 {
+        let entry = |i: usize| -> Float {
+            (0..3).map(|j| self.element(j, i) * vector.component(j)).sum()
+        };
+
+        Vec3::new(entry(0), entry(1), entry(2))
+    }
+
+    /// The entry in row `row` and column `column`, both counting from 0.
+    pub fn element(&self, row: usize, column: usize) -> Float {
+        // cgmath stores matrices as an array of columns
+        self.internal[column][row]
+    }
 }
 
 // S==== CONSTRUCTING TRANSFORMATIONS {{{2
@@ -5,4 +5,6 @@ pub mod vector;
 pub mod ray;
 pub mod orthonormal_basis;
 pub mod matrix;
+pub mod aabb;
 
+pub mod noise;
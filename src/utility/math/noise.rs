@@ -0,0 +1,135 @@
+//! Perlin's gradient noise, and the fractal sums of it that procedural textures are
+//! built from.
+
+use crate::utility::rng::RandomNumberGenerator;
+use super::{float::Float, vector::Point3};
+
+const TABLE_SIZE: usize = 256;
+
+/// Perlin's "improved noise": a smooth function of 3D space that varies on the scale of
+/// the integer lattice, with values in about $[-1, 1]$. It vanishes on the lattice
+/// points, where a pseudo-random gradient is attached, and interpolates the linear
+/// functions given by the gradients of the 8 lattice points around a point.
+#[derive(Debug)]
+pub struct Perlin {
+    /// A permutation of $0, \ldots, 255$ repeated twice, so that it can be indexed by
+    /// sums of a table entry and a coordinate without wrapping.
+    permutation: Vec<usize>,
+}
+
+impl Perlin {
+    /// The lattice gradients are picked by a permutation shuffled with `seed`.
+    pub fn new(seed: u32) -> Self {
+        let mut rng = RandomNumberGenerator::from_seed(seed);
+        let mut permutation: Vec<usize> = (0..TABLE_SIZE).collect();
+        // Fisher-Yates shuffle.
+        for i in (1..TABLE_SIZE).rev() {
+            let j = usize::min(i, (rng.next_float() * (i + 1) as Float) as usize);
+            permutation.swap(i, j);
+        }
+        permutation.extend_from_within(..);
+
+        Self { permutation }
+    }
+
+    pub fn noise(&self, point: &Point3) -> Float {
+        let coordinates = [point.x(), point.y(), point.z()];
+        let cell = coordinates.map(|c| c.floor());
+        // Which lattice cell we are in (wrapped to the table size), and where in it.
+        let [xi, yi, zi] = cell.map(|c| (c as i64).rem_euclid(TABLE_SIZE as i64) as usize);
+        let [x, y, z] = [0, 1, 2].map(|i| coordinates[i] - cell[i]);
+        let [u, v, w] = [x, y, z].map(fade);
+
+        let p = &self.permutation;
+        let a = p[xi] + yi;
+        let (aa, ab) = (p[a] + zi, p[a + 1] + zi);
+        let b = p[xi + 1] + yi;
+        let (ba, bb) = (p[b] + zi, p[b + 1] + zi);
+
+        lerp(w,
+            lerp(v,
+                lerp(u, gradient(p[aa], x, y, z), gradient(p[ba], x - 1.0, y, z)),
+                lerp(u, gradient(p[ab], x, y - 1.0, z), gradient(p[bb], x - 1.0, y - 1.0, z))
+            ),
+            lerp(v,
+                lerp(u, gradient(p[aa + 1], x, y, z - 1.0), gradient(p[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(u, gradient(p[ab + 1], x, y - 1.0, z - 1.0), gradient(p[bb + 1], x - 1.0, y - 1.0, z - 1.0))
+            )
+        )
+    }
+
+    /// Fractional Brownian motion: `octaves` layers of noise, each at twice the frequency
+    /// and half the amplitude of the last. Also in about $[-1, 1]$.
+    pub fn fbm(&self, point: &Point3, octaves: u32) -> Float {
+        self.sum_octaves(point, octaves, |noise| noise)
+    }
+
+    /// Like `fbm()`, but summing the absolute values of the layers, which gives creases
+    /// where the noise changes sign. In about $[0, 1]$.
+    pub fn turbulence(&self, point: &Point3, octaves: u32) -> Float {
+        self.sum_octaves(point, octaves, Float::abs)
+    }
+
+    /// The sum of `f` of each layer, normalized by the sum of the amplitudes.
+    fn sum_octaves(&self, point: &Point3, octaves: u32, f: impl Fn(Float) -> Float) -> Float {
+        let (mut sum, mut total_amplitude) = (0.0, 0.0);
+        let (mut amplitude, mut frequency) = (1.0, 1.0);
+        for _ in 0..u32::max(1, octaves) {
+            sum += amplitude * f(self.noise(&(frequency * point)));
+            total_amplitude += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+
+        sum / total_amplitude
+    }
+}
+
+/// $6t^5 - 15t^4 + 10t^3$, which eases in and out of the lattice points with vanishing
+/// first and second derivatives.
+fn fade(t: Float) -> Float {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: Float, a: Float, b: Float) -> Float {
+    a + t * (b - a)
+}
+
+/// The dot product of $(x, y, z)$ with one of the 12 vectors from the center of a cube
+/// to the middles of its edges, picked by `hash`.
+fn gradient(hash: usize, x: Float, y: Float, z: Float) -> Float {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_bounded_and_vanishes_on_the_lattice() {
+        let perlin = Perlin::new(1);
+        let mut rng = RandomNumberGenerator::from_seed(2);
+
+        assert_eq!(perlin.noise(&Point3::new(3.0, -2.0, 7.0)), 0.0);
+        for _ in 0..10_000 {
+            let point = Point3::new(
+                100.0 * rng.next_float() - 50.0,
+                100.0 * rng.next_float() - 50.0,
+                100.0 * rng.next_float() - 50.0
+            );
+            assert!(perlin.noise(&point).abs() <= 1.1);
+            assert!(perlin.fbm(&point, 6).abs() <= 1.1);
+            assert!((0.0..=1.1).contains(&perlin.turbulence(&point, 6)));
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use super::{vector::{Point3, Vec3}, float::{Float, FLOAT_ERR}};
+use super::{vector::{Point3, Vec3, Normal3}, float::{Float, FLOAT_ERR}};
+
+/// How far, relative to the magnitude of its coordinates, we push the origin of a ray 
+/// leaving a surface off of that surface.
+const SURFACE_OFFSET_EPSILON: Float = 0.0001;
 
 
 #[derive(Clone, Debug, Default)]
@@ -7,6 +11,18 @@ pub struct Ray3 {
     pub direction: Vec3,
     pub min_t: Float,
     pub max_t: Float,
+    /// Camera rays carry these, so that what they hit can tell how large an area of the
+    /// surface one pixel covers. Other rays don't.
+    pub differentials: Option<RayDifferentials>,
+}
+
+/// The rays through the neighboring pixels, one over in $x$ and one over in $y$.
+#[derive(Clone, Debug, Default)]
+pub struct RayDifferentials {
+    pub rx_origin: Point3,
+    pub rx_direction: Vec3,
+    pub ry_origin: Point3,
+    pub ry_direction: Vec3,
 }
 
 impl Ray3 {
@@ -16,9 +32,27 @@ impl Ray3 {
             direction,
             min_t: FLOAT_ERR,
             max_t: Float::INFINITY,
+            differentials: None,
         }
     }
 
+    /// A ray leaving the surface at `point` in `direction`. Due to floating point error, 
+    /// `point` may lie slightly on the wrong side of the surface, so we push the origin
+    /// off the surface (along `surface_normal`, towards `direction`) so that the ray 
+    /// does not immediately hit the surface it is leaving.
+    pub fn new_from_surface(point: &Point3, surface_normal: &Normal3, direction: Vec3) -> Self {
+        let magnitude = Float::max(
+            1.0, 
+            Float::max(Float::abs(point.x()), Float::max(Float::abs(point.y()), Float::abs(point.z())))
+        );
+        let offset = {
+            let normal = surface_normal.facing(&direction);
+            (SURFACE_OFFSET_EPSILON * magnitude) * normal.as_vec3()
+        };
+
+        Self::new(point + offset, direction)
+    }
+
     pub fn is_in_range(&self, t: Float) -> bool {
         (self.min_t < t) && (t < self.max_t)
     }
@@ -47,6 +47,9 @@ impl Vec3 {
     /// Retrieve the z coordinate.
     pub fn z(&self) -> Float { self.internal.z }
 
+    /// Retrieve the coordinate by index, i.e. 0 for x, 1 for y and 2 for z.
+    pub fn component(&self, index: usize) -> Float { self.internal[index] }
+
     /// Change the x coordinate to the specified value.
     pub fn set_x(&mut self, x: Float) { self.internal.x = x; }
     /// Change the y coordinate to the specified value.
@@ -54,6 +57,11 @@ impl Vec3 {
     /// Change the z coordinate to the specified value.
     pub fn set_z(&mut self, z: Float) { self.internal.z = z; }
 
+    /// The Euclidean length of the vector.
+    pub fn length(&self) -> Float {
+        self.internal.magnitude()
+    }
+
     pub fn normalize(mut self) -> Self {
         self.internal = self.internal.normalize();
         self
@@ -81,6 +89,12 @@ pub fn cross(v1: &Vec3, v2: &Vec3) -> Vec3 {
     }
 }
 
+/// The mirror image of `v` about the line through `normal`, which must be unit length.
+/// A vector pointing towards a surface is reflected to point away from it.
+pub fn reflect(v: &Vec3, normal: &Vec3) -> Vec3 {
+    v - (2.0 * dot(v, normal)) * normal
+}
+
 impl Default for Vec3 {
     fn default() -> Self {
         Vec3::new(0.0, 0.0, 0.0)
@@ -187,6 +201,42 @@ impl ops::Mul<&Vec3> for Float {
     }
 }
 
+// Vec3 * Float
+impl ops::Mul<Float> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Float) -> Self::Output {
+        Vec3::new(self.x() * rhs, self.y() * rhs, self.z() * rhs)
+    }
+}
+
+// Vec3 * Vec3 (componentwise, which is what we want when combining colors)
+impl ops::Mul<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Self::Output {
+        Vec3::new(self.x() * rhs.x(), self.y() * rhs.y(), self.z() * rhs.z())
+    }
+}
+
+// Vec3 * &Vec3 (componentwise)
+impl ops::Mul<&Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: &Vec3) -> Self::Output {
+        Vec3::new(self.x() * rhs.x(), self.y() * rhs.y(), self.z() * rhs.z())
+    }
+}
+
+// &Vec3 * &Vec3 (componentwise)
+impl ops::Mul<&Vec3> for &Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: &Vec3) -> Self::Output {
+        Vec3::new(self.x() * rhs.x(), self.y() * rhs.y(), self.z() * rhs.z())
+    }
+}
+
 // Vec3 / Float 
 impl ops::Div<Float> for Vec3 {
     type Output = Vec3;
@@ -224,6 +274,58 @@ impl Point3 {
     }
 }
 
+// S==== NORMAL {{{1
+
+/// A surface normal. These are kept apart from other vectors because they transform
+/// differently: a normal has to stay perpendicular to the (transformed) tangent vectors
+/// of its surface, so it is transformed by the inverse transpose of the matrix that 
+/// transforms vectors (see `Transform::normal_to_global`).
+#[derive(Clone, Debug, Default)]
+pub struct Normal3 {
+    internal: Vec3,
+}
+
+impl Normal3 {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
+        Normal3 { internal: Vec3::new(x, y, z) }
+    }
+
+    /// Treats `vector` as a normal. It is up to the caller to make sure that it is 
+    /// perpendicular to the surface.
+    pub fn new_from_vec3(vector: Vec3) -> Self {
+        Normal3 { internal: vector }
+    }
+
+    pub fn as_vec3(&self) -> &Vec3 {
+        &self.internal
+    }
+
+    pub fn x(&self) -> Float { self.internal.x() }
+    pub fn y(&self) -> Float { self.internal.y() }
+    pub fn z(&self) -> Float { self.internal.z() }
+
+    pub fn normalize(self) -> Self {
+        Normal3 { internal: self.internal.normalize() }
+    }
+
+    /// The normal pointing into the same side of the surface as `direction`.
+    pub fn facing(&self, direction: &Vec3) -> Self {
+        if dot(&self.internal, direction) < 0.0 {
+            Normal3 { internal: -1.0 * &self.internal }
+        } else {
+            self.clone()
+        }
+    }
+}
+
+impl From<Normal3> for Vec3 {
+    fn from(normal: Normal3) -> Self {
+        normal.internal
+    }
+}
+
+// E==== NORMAL }}}1
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,5 +3,9 @@
 pub mod math;
 pub mod image;
 pub mod rng;
+pub mod post_process;
+pub mod obj;
+pub mod ply;
+pub mod interrupt;
 pub mod scene_parser;
 
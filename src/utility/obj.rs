@@ -0,0 +1,218 @@
+//! Reading Wavefront OBJ files. We only read the geometry: vertex positions (`v`),
+//! texture coordinates (`vt`), normals (`vn`), faces (`f`) and the groups (`g`) or
+//! objects (`o`) the faces belong to. Everything else (materials, smoothing groups,
+//! curves, ...) is skipped.
+//!
+//! Faces with more than three vertices are assumed to be convex, and are split into a
+//! fan of triangles.
+
+// S==== IMPORTS {{{1
+
+use std::fs::read_to_string;
+use super::math::{float::Float, vector::{Point3, Vec3}};
+
+// E==== IMPORTS }}}1
+
+/// The name of the group faces belong to before any `g` or `o` statement.
+pub const DEFAULT_GROUP_NAME: &str = "default";
+
+/// A corner of a face, as indices into the buffers of `ObjData`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjVertex {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ObjGroup {
+    pub name: String,
+    pub triangles: Vec<[ObjVertex; 3]>,
+}
+
+/// The contents of an OBJ file. The buffers are shared by all groups.
+#[derive(Debug, Default)]
+pub struct ObjData {
+    pub positions: Vec<Point3>,
+    pub uvs: Vec<(Float, Float)>,
+    pub normals: Vec<Vec3>,
+    /// In the order they first appear in the file. Groups without faces are left out.
+    pub groups: Vec<ObjGroup>,
+}
+
+pub fn load_obj(filename: &str) -> Result<ObjData, String> {
+    let source = read_to_string(filename)
+        .map_err(|e| format!("could not read '{}': {}", filename, e))?;
+
+    parse_obj(&source).map_err(|e| format!("in '{}': {}", filename, e))
+}
+
+pub fn parse_obj(source: &str) -> Result<ObjData, String> {
+    let mut data = ObjData::default();
+    let mut current_group = DEFAULT_GROUP_NAME.to_string();
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => line,
+        };
+
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => { continue; }
+        };
+        let arguments: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(&arguments, line_number)?;
+                data.positions.push(Point3::new(x, y, z));
+            },
+            "vt" => {
+                // A third (w) coordinate is allowed, but we have no use for it.
+                let [u, v] = parse_floats::<2>(&arguments, line_number)?;
+                data.uvs.push((u, v));
+            },
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(&arguments, line_number)?;
+                data.normals.push(Vec3::new(x, y, z));
+            },
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(format!("line {}: a face needs at least 3 vertices", line_number));
+                }
+
+                let vertices = arguments.iter()
+                    .map(|argument| parse_face_vertex(argument, &data, line_number))
+                    .collect::<Result<Vec<ObjVertex>, String>>()?;
+
+                let group = group_named(&mut data.groups, &current_group);
+                for i in 1..(vertices.len() - 1) {
+                    group.triangles.push([
+                        vertices[0].clone(),
+                        vertices[i].clone(),
+                        vertices[i + 1].clone()
+                    ]);
+                }
+            },
+            "g" | "o" => {
+                current_group = if arguments.is_empty() {
+                    DEFAULT_GROUP_NAME.to_string()
+                } else {
+                    arguments.join(" ")
+                };
+            },
+            _ => {},
+        }
+    }
+
+    Ok(data)
+}
+
+/// The group called `name`, which is created if there is none yet.
+fn group_named<'a>(groups: &'a mut Vec<ObjGroup>, name: &str) -> &'a mut ObjGroup {
+    let index = match groups.iter().position(|group| group.name == name) {
+        Some(index) => index,
+        None => {
+            groups.push(ObjGroup { name: name.to_string(), triangles: Vec::new() });
+            groups.len() - 1
+        }
+    };
+
+    &mut groups[index]
+}
+
+/// The first `N` arguments as floats. Any further arguments are ignored.
+fn parse_floats<const N: usize>(arguments: &[&str], line_number: usize) -> Result<[Float; N], String> {
+    if arguments.len() < N {
+        return Err(format!("line {}: expected {} numbers", line_number, N));
+    }
+
+    let mut to_return = [0.0; N];
+    for i in 0..N {
+        to_return[i] = arguments[i].parse::<Float>()
+            .map_err(|_| format!("line {}: '{}' is not a number", line_number, arguments[i]))?;
+    }
+
+    Ok(to_return)
+}
+
+/// Parses `position`, `position/uv`, `position//normal` or `position/uv/normal`. The
+/// indices in the file start at 1, and negative ones count back from the latest element.
+fn parse_face_vertex(argument: &str, data: &ObjData, line_number: usize) -> Result<ObjVertex, String> {
+    let mut parts = argument.split('/');
+
+    let mut resolve = |count: usize, what: &str| -> Result<Option<usize>, String> {
+        let part = match parts.next() {
+            Some(part) if !part.is_empty() => part,
+            _ => { return Ok(None); }
+        };
+
+        let index = part.parse::<i64>()
+            .map_err(|_| format!("line {}: invalid {} index '{}'", line_number, what, part))?;
+        let resolved = if index > 0 {
+            index - 1
+        } else {
+            count as i64 + index
+        };
+
+        if index == 0 || resolved < 0 || resolved >= count as i64 {
+            return Err(format!("line {}: {} index {} is out of range", line_number, what, index));
+        }
+        Ok(Some(resolved as usize))
+    };
+
+    let position = resolve(data.positions.len(), "position")?
+        .ok_or(format!("line {}: face vertex '{}' has no position", line_number, argument))?;
+    let uv = resolve(data.uvs.len(), "texture coordinate")?;
+    let normal = resolve(data.normals.len(), "normal")?;
+
+    Ok(ObjVertex { position, uv, normal })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_groups_and_triangulates() {
+        let source = "
+            # a square, split across two groups
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vt 0 0
+            vt 1 1
+            vn 0 0 1
+
+            f 1/1/1 2//1 3/2/1
+            g top
+            f -4 -2 -1 # relative indices
+            g quad
+            f 1 2 3 4
+        ";
+        let data = parse_obj(source).unwrap();
+
+        assert_eq!(data.positions.len(), 4);
+        assert_eq!(data.uvs.len(), 2);
+        assert_eq!(data.normals.len(), 1);
+
+        let names: Vec<&str> = data.groups.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, vec![DEFAULT_GROUP_NAME, "top", "quad"]);
+
+        let first = &data.groups[0].triangles[0];
+        assert_eq!(first[0], ObjVertex { position: 0, uv: Some(0), normal: Some(0) });
+        assert_eq!(first[1], ObjVertex { position: 1, uv: None, normal: Some(0) });
+
+        let relative = &data.groups[1].triangles[0];
+        let positions: Vec<usize> = relative.iter().map(|vertex| vertex.position).collect();
+        assert_eq!(positions, vec![0, 2, 3]);
+
+        assert_eq!(data.groups[2].triangles.len(), 2);
+
+        assert!(parse_obj("v 0 0 0\nf 1 2 3").is_err());
+    }
+}
@@ -0,0 +1,431 @@
+//! Reading Stanford PLY files, in the ASCII or binary little-endian format. We read the
+//! `vertex` element's positions (`x`, `y`, `z`) and, if present, normals (`nx`, `ny`,
+//! `nz`), texture coordinates (`u`, `v`, or `s`, `t`) and colors (`red`, `green`, `blue`),
+//! along with the `vertex_indices` list of the `face` element. Other elements and
+//! properties are skipped.
+//!
+//! Faces with more than three vertices are assumed to be convex, and are split into a
+//! fan of triangles.
+
+// S==== IMPORTS {{{1
+
+use std::fs::read;
+use super::{
+    math::{float::Float, vector::{Point3, Vec3, Color3}},
+    post_process::srgb_oetf_inverse
+};
+
+// E==== IMPORTS }}}1
+
+/// The contents of a PLY file. Each vertex attribute is either given for every vertex
+/// or empty, and the triangles index all of them alike.
+#[derive(Debug, Default)]
+pub struct PlyData {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(Float, Float)>,
+    /// Linear colors. Colors stored as integers are taken to be sRGB encoded.
+    pub colors: Vec<Color3>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+#[derive(Debug)]
+enum PropertyKind {
+    Scalar(ScalarType),
+    /// A count of type `count`, followed by that many values of type `item`.
+    List { count: ScalarType, item: ScalarType },
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+pub fn load_ply(filename: &str) -> Result<PlyData, String> {
+    let bytes = read(filename)
+        .map_err(|e| format!("could not read '{}': {}", filename, e))?;
+
+    parse_ply(&bytes).map_err(|e| format!("in '{}': {}", filename, e))
+}
+
+pub fn parse_ply(bytes: &[u8]) -> Result<PlyData, String> {
+    let (format, elements, body) = parse_header(bytes)?;
+    let mut reader = BodyReader::new(format, body);
+    let mut data = PlyData::default();
+
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => read_vertices(element, &mut reader, &mut data)?,
+            "face" => read_faces(element, &mut reader, &mut data)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in element.properties.iter() {
+                        reader.read_property(&property.kind)?;
+                    }
+                }
+            },
+        }
+    }
+
+    let num_vertices = data.positions.len();
+    if let Some(index) = data.triangles.iter().flatten().find(|&&index| index >= num_vertices) {
+        return Err(format!("face refers to vertex {}, but there are only {}", index, num_vertices));
+    }
+
+    Ok(data)
+}
+
+// S==== HEADER {{{1
+
+/// Returns the format, the elements in the order their data appears, and the data.
+fn parse_header(bytes: &[u8]) -> Result<(Format, Vec<Element>, &[u8]), String> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut position = 0;
+    let mut first_line = true;
+
+    loop {
+        let line_end = bytes[position..].iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or("the header is not terminated by 'end_header'")?;
+        let line = std::str::from_utf8(&bytes[position..(position + line_end)])
+            .map_err(|_| "the header is not valid text")?
+            .trim();
+        position += line_end + 1;
+
+        if first_line {
+            if line != "ply" {
+                return Err("not a PLY file".to_string());
+            }
+            first_line = false;
+            continue;
+        }
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["end_header"] => { break; },
+            ["format", "ascii", _] => { format = Some(Format::Ascii); },
+            ["format", "binary_little_endian", _] => { format = Some(Format::BinaryLittleEndian); },
+            ["format", other, ..] => {
+                return Err(format!("unsupported format '{}'", other));
+            },
+            ["element", name, count] => {
+                let count = count.parse::<usize>()
+                    .map_err(|_| format!("invalid count for element '{}'", name))?;
+                elements.push(Element { name: name.to_string(), count, properties: Vec::new() });
+            },
+            ["property", "list", count, item, name] => {
+                let kind = PropertyKind::List {
+                    count: parse_scalar_type(count)?,
+                    item: parse_scalar_type(item)?,
+                };
+                add_property(&mut elements, name, kind)?;
+            },
+            ["property", scalar, name] => {
+                let kind = PropertyKind::Scalar(parse_scalar_type(scalar)?);
+                add_property(&mut elements, name, kind)?;
+            },
+            ["comment", ..] | ["obj_info", ..] | [] => {},
+            _ => {
+                return Err(format!("unexpected line '{}' in the header", line));
+            },
+        }
+    }
+
+    let format = format.ok_or("the header does not give the format")?;
+    Ok((format, elements, &bytes[position..]))
+}
+
+fn add_property(elements: &mut [Element], name: &str, kind: PropertyKind) -> Result<(), String> {
+    let element = elements.last_mut()
+        .ok_or(format!("property '{}' comes before any element", name))?;
+    element.properties.push(Property { name: name.to_string(), kind });
+
+    Ok(())
+}
+
+fn parse_scalar_type(name: &str) -> Result<ScalarType, String> {
+    match name {
+        "char" | "int8" => Ok(ScalarType::Int8),
+        "uchar" | "uint8" => Ok(ScalarType::UInt8),
+        "short" | "int16" => Ok(ScalarType::Int16),
+        "ushort" | "uint16" => Ok(ScalarType::UInt16),
+        "int" | "int32" => Ok(ScalarType::Int32),
+        "uint" | "uint32" => Ok(ScalarType::UInt32),
+        "float" | "float32" => Ok(ScalarType::Float32),
+        "double" | "float64" => Ok(ScalarType::Float64),
+        other => Err(format!("unknown property type '{}'", other)),
+    }
+}
+
+impl ScalarType {
+    fn size_in_bytes(&self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+
+    /// The largest value of integer types, which stands for full intensity in colors.
+    fn integer_max(&self) -> Option<f64> {
+        match self {
+            ScalarType::Int8 => Some(i8::MAX as f64),
+            ScalarType::UInt8 => Some(u8::MAX as f64),
+            ScalarType::Int16 => Some(i16::MAX as f64),
+            ScalarType::UInt16 => Some(u16::MAX as f64),
+            ScalarType::Int32 => Some(i32::MAX as f64),
+            ScalarType::UInt32 => Some(u32::MAX as f64),
+            ScalarType::Float32 | ScalarType::Float64 => None,
+        }
+    }
+}
+
+// E==== HEADER }}}1
+
+// S==== BODY {{{1
+
+/// Reads the values of the body one at a time, whatever the format.
+struct BodyReader<'a> {
+    format: Format,
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BodyReader<'a> {
+    fn new(format: Format, bytes: &'a [u8]) -> Self {
+        BodyReader { format, bytes, position: 0 }
+    }
+
+    fn read_scalar(&mut self, scalar_type: ScalarType) -> Result<f64, String> {
+        match self.format {
+            Format::Ascii => self.read_ascii_scalar(),
+            Format::BinaryLittleEndian => self.read_binary_scalar(scalar_type),
+        }
+    }
+
+    fn read_ascii_scalar(&mut self) -> Result<f64, String> {
+        let rest = &self.bytes[self.position..];
+        let start = rest.iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .ok_or("the file ended early")?;
+        let length = rest[start..].iter()
+            .position(|byte| byte.is_ascii_whitespace())
+            .unwrap_or(rest.len() - start);
+        self.position += start + length;
+
+        let token = String::from_utf8_lossy(&rest[start..(start + length)]);
+        token.parse::<f64>().map_err(|_| format!("'{}' is not a number", token))
+    }
+
+    fn read_binary_scalar(&mut self, scalar_type: ScalarType) -> Result<f64, String> {
+        let size = scalar_type.size_in_bytes();
+        let bytes = self.bytes.get(self.position..(self.position + size))
+            .ok_or("the file ended early")?;
+        self.position += size;
+
+        // The slice has exactly the right length, so the conversions can't fail.
+        let value = match scalar_type {
+            ScalarType::Int8 => i8::from_le_bytes([bytes[0]]) as f64,
+            ScalarType::UInt8 => bytes[0] as f64,
+            ScalarType::Int16 => i16::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            ScalarType::UInt16 => u16::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            ScalarType::Int32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            ScalarType::UInt32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            ScalarType::Float32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            ScalarType::Float64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+        };
+        Ok(value)
+    }
+
+    /// The values of a property. Scalars give a single value.
+    fn read_property(&mut self, kind: &PropertyKind) -> Result<Vec<f64>, String> {
+        match kind {
+            PropertyKind::Scalar(scalar_type) => Ok(vec![self.read_scalar(*scalar_type)?]),
+            PropertyKind::List { count, item } => {
+                let count = self.read_scalar(*count)?;
+                if count < 0.0 {
+                    return Err("a list has a negative length".to_string());
+                }
+
+                (0..(count as usize)).map(|_| self.read_scalar(*item)).collect()
+            },
+        }
+    }
+}
+
+fn read_vertices(element: &Element, reader: &mut BodyReader, data: &mut PlyData) -> Result<(), String> {
+    let find = |names: &[&str]| -> Option<usize> {
+        element.properties.iter().position(|property| names.contains(&property.name.as_str()))
+    };
+    let find_all = |names: &[&[&str]]| -> Option<Vec<usize>> {
+        names.iter().map(|alternatives| find(alternatives)).collect()
+    };
+
+    let position_indices = find_all(&[&["x"], &["y"], &["z"]])
+        .ok_or("vertices must have the properties 'x', 'y' and 'z'")?;
+    let normal_indices = find_all(&[&["nx"], &["ny"], &["nz"]]);
+    let uv_indices = find_all(&[&["u", "s", "texture_u"], &["v", "t", "texture_v"]]);
+    let color_indices = find_all(&[&["red"], &["green"], &["blue"]]);
+
+    // Integer colors are scaled to [0, 1] and decoded from sRGB.
+    let color_decoding: Vec<Option<f64>> = color_indices.iter()
+        .flatten()
+        .map(|&index| match &element.properties[index].kind {
+            PropertyKind::Scalar(scalar_type) => scalar_type.integer_max(),
+            PropertyKind::List { .. } => None,
+        })
+        .collect();
+
+    for _ in 0..element.count {
+        let mut values: Vec<f64> = Vec::with_capacity(element.properties.len());
+        for property in element.properties.iter() {
+            // Lists don't make sense for the attributes we read, so we only keep their
+            // first value to keep the indices of the properties intact.
+            let property_values = reader.read_property(&property.kind)?;
+            values.push(property_values.first().copied().unwrap_or(0.0));
+        }
+        let get = |index: usize| values[index] as Float;
+
+        data.positions.push(Point3::new(
+            get(position_indices[0]), get(position_indices[1]), get(position_indices[2])
+        ));
+        if let Some(indices) = &normal_indices {
+            data.normals.push(Vec3::new(get(indices[0]), get(indices[1]), get(indices[2])));
+        }
+        if let Some(indices) = &uv_indices {
+            data.uvs.push((get(indices[0]), get(indices[1])));
+        }
+        if let Some(indices) = &color_indices {
+            let channels: Vec<Float> = indices.iter()
+                .zip(color_decoding.iter())
+                .map(|(&index, decoding)| match decoding {
+                    Some(max) => srgb_oetf_inverse((values[index] / max) as Float),
+                    None => get(index),
+                })
+                .collect();
+            data.colors.push(Color3::new(channels[0], channels[1], channels[2]));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_faces(element: &Element, reader: &mut BodyReader, data: &mut PlyData) -> Result<(), String> {
+    let indices_property = element.properties.iter()
+        .position(|property| property.name == "vertex_indices" || property.name == "vertex_index")
+        .ok_or("faces must have the property 'vertex_indices'")?;
+
+    for _ in 0..element.count {
+        for (property_index, property) in element.properties.iter().enumerate() {
+            let values = reader.read_property(&property.kind)?;
+            if property_index != indices_property {
+                continue;
+            }
+
+            if values.len() < 3 {
+                return Err("a face needs at least 3 vertices".to_string());
+            }
+            if values.iter().any(|&value| value < 0.0) {
+                return Err("a face has a negative vertex index".to_string());
+            }
+
+            let indices: Vec<usize> = values.iter().map(|&value| value as usize).collect();
+            for i in 1..(indices.len() - 1) {
+                data.triangles.push([indices[0], indices[i], indices[i + 1]]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// E==== BODY }}}1
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER_ELEMENTS: &str = "\
+comment a unit square with colored corners
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+";
+
+    #[test]
+    fn ascii_and_binary_agree() {
+        let ascii = format!("ply\nformat ascii 1.0\n{}{}", HEADER_ELEMENTS, "\
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+");
+
+        let mut binary = format!("ply\nformat binary_little_endian 1.0\n{}", HEADER_ELEMENTS)
+            .into_bytes();
+        let corners: [([f32; 3], [u8; 3]); 4] = [
+            ([0.0, 0.0, 0.0], [255, 0, 0]),
+            ([1.0, 0.0, 0.0], [0, 255, 0]),
+            ([1.0, 1.0, 0.0], [0, 0, 255]),
+            ([0.0, 1.0, 0.0], [255, 255, 255]),
+        ];
+        for (position, color) in corners {
+            for coordinate in position {
+                binary.extend_from_slice(&coordinate.to_le_bytes());
+            }
+            binary.extend_from_slice(&color);
+        }
+        binary.push(4);
+        for index in [0i32, 1, 2, 3] {
+            binary.extend_from_slice(&index.to_le_bytes());
+        }
+
+        for data in [parse_ply(ascii.as_bytes()).unwrap(), parse_ply(&binary).unwrap()] {
+            assert_eq!(data.positions.len(), 4);
+            assert!(Vec3::are_equal(&data.positions[2], &Point3::new(1.0, 1.0, 0.0)));
+            assert!(data.normals.is_empty() && data.uvs.is_empty());
+            assert!(Vec3::are_equal(&data.colors[3], &Color3::new(1.0, 1.0, 1.0)));
+            assert!(Vec3::are_equal(&data.colors[1], &Color3::new(0.0, 1.0, 0.0)));
+            assert_eq!(data.triangles, vec![[0, 1, 2], [0, 2, 3]]);
+        }
+
+        let out_of_range = ascii.replace("4 0 1 2 3", "3 0 1 4");
+        assert!(parse_ply(out_of_range.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Turning the linear radiance computed by the integrator into values fit for a file
+//! that will be looked at. The steps are applied in the order
+//!
+//! 1. exposure, scaling the image by a power of 2,
+//! 2. tone mapping, compressing the unbounded radiance into $[0, 1]$,
+//! 3. the transfer function (e.g. sRGB), encoding the values for display,
+//! 4. clamping to $[0, 1]$.
+//!
+//! With the default settings, the image is left unchanged.
+
+// S==== IMPORTS {{{1
+
+use super::{
+    image::Image,
+    math::{float::Float, vector::Color3}
+};
+
+// E==== IMPORTS }}}1
+
+#[derive(Clone, Copy, Debug)]
+pub enum ToneMapping {
+    None,
+    /// Reinhard's operator $L \mapsto L / (1 + L)$, applied to the luminance so that hues
+    /// are preserved.
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve, applied to each channel.
+    AcesFilmic,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TransferFunction {
+    /// Keep the values linear.
+    Linear,
+    /// The sRGB OETF, as expected by most displays and 8-bit image formats.
+    Srgb,
+}
+
+#[derive(Clone, Debug)]
+pub struct PostProcess {
+    /// In stops, so each unit doubles the brightness.
+    pub exposure: Float,
+    pub tone_mapping: ToneMapping,
+    pub transfer_function: TransferFunction,
+    pub clamp: bool,
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            tone_mapping: ToneMapping::None,
+            transfer_function: TransferFunction::Linear,
+            clamp: false,
+        }
+    }
+}
+
+impl PostProcess {
+    pub fn apply(&self, image: &Image) -> Image {
+        let mut to_return = image.clone();
+
+        for pixel in image.get_resolution().into_iter() {
+            let color = self.apply_to_color(image.get_pixel_color(&pixel));
+            to_return.set_pixel_color(&pixel, color);
+        }
+
+        to_return
+    }
+
+    pub fn apply_to_color(&self, color: Color3) -> Color3 {
+        let exposed = Float::powf(2.0, self.exposure) * color;
+
+        let tone_mapped = match self.tone_mapping {
+            ToneMapping::None => exposed,
+            ToneMapping::Reinhard => reinhard(exposed),
+            ToneMapping::AcesFilmic => map_channels(exposed, aces_filmic),
+        };
+
+        let encoded = match self.transfer_function {
+            TransferFunction::Linear => tone_mapped,
+            TransferFunction::Srgb => map_channels(tone_mapped, srgb_oetf),
+        };
+
+        if self.clamp {
+            map_channels(encoded, |channel| Float::clamp(channel, 0.0, 1.0))
+        } else {
+            encoded
+        }
+    }
+}
+
+fn map_channels(color: Color3, f: impl Fn(Float) -> Float) -> Color3 {
+    Color3::new(f(color.x()), f(color.y()), f(color.z()))
+}
+
+/// The luminance of a linear color with Rec. 709 (equivalently sRGB) primaries.
+pub fn luminance(color: &Color3) -> Float {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
+pub fn reinhard(color: Color3) -> Color3 {
+    let luminance = luminance(&color);
+    if luminance <= 0.0 {
+        return color;
+    }
+
+    (1.0 / (1.0 + luminance)) * color
+}
+
+/// Narkowicz's rational fit of the ACES reference rendering transform. The fit was made
+/// for inputs exposed so that scene white is about 0.6, which we adjust for here, so
+/// that an exposure of 0 gives roughly the same brightness as the other operators.
+pub fn aces_filmic(x: Float) -> Float {
+    let x = 0.6 * x;
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+
+    Float::clamp((x * (a * x + b)) / (x * (c * x + d) + e), 0.0, 1.0)
+}
+
+/// The sRGB opto-electronic transfer function, taking linear values to the values stored
+/// in sRGB images. Negative values are mapped to 0.
+pub fn srgb_oetf(linear: Float) -> Float {
+    if linear <= 0.0 {
+        0.0
+    } else if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * Float::powf(linear, 1.0 / 2.4) - 0.055
+    }
+}
+
+/// The inverse of `srgb_oetf()`, taking values stored in sRGB images back to linear.
+pub fn srgb_oetf_inverse(encoded: Float) -> Float {
+    if encoded <= 0.0 {
+        0.0
+    } else if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        Float::powf((encoded + 0.055) / 1.055, 2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_transform_stays_in_unit_range() {
+        let post_process = PostProcess {
+            exposure: 1.0,
+            tone_mapping: ToneMapping::Reinhard,
+            transfer_function: TransferFunction::Srgb,
+            clamp: true,
+        };
+
+        for value in [0.0, 0.001, 0.18, 1.0, 10.0, 1.0e6] {
+            let mapped = post_process.apply_to_color(Color3::new(value, 0.5 * value, 0.0));
+            for channel in [mapped.x(), mapped.y(), mapped.z()] {
+                assert!((0.0..=1.0).contains(&channel));
+            }
+        }
+
+        assert!((srgb_oetf(1.0) - 1.0).abs() < 1e-5);
+        assert!((srgb_oetf_inverse(srgb_oetf(0.18)) - 0.18).abs() < 1e-5);
+        assert!((aces_filmic(1.0e6) - 1.0).abs() < 1e-2);
+    }
+}
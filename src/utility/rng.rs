@@ -20,6 +20,20 @@ impl RandomNumberGenerator {
         }
     }
 
+    /// Produces `count` independent generators, e.g. one for each thread. Their seeds are
+    /// drawn from this generator, so the same generator always splits the same way.
+    pub fn split(&mut self, count: usize) -> Vec<Self> {
+        (0..count)
+            .map(|_| {
+                let seed = ((self.internal.next_u64() as u128) << 64) 
+                    | (self.internal.next_u64() as u128);
+                RandomNumberGenerator {
+                    internal: rand_pcg::Pcg64Mcg::new(seed),
+                }
+            })
+            .collect()
+    }
+
     pub fn next_float(&mut self) -> Float {
         match Float::kind() {
             KindOfFloat::Float32 => {